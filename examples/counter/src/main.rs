@@ -1,8 +1,9 @@
+use iced::time::Duration;
 use iced::widget::{
     button, column, container, float, horizontal_space, row, slider, stack, text, toggler,
 };
 use iced::{Center, Element};
-use iced_blur::blur;
+use iced_blur::{Easing, blur};
 
 pub fn main() -> iced::Result {
     iced::run(Counter::update, Counter::view)
@@ -71,7 +72,9 @@ impl Counter {
             stack![
                 background,
                 float(container(text("mid").size(20)).padding(10)),
-                self.show.then(|| blur(self.radius)),
+                self.show.then(|| {
+                    blur(self.radius).animate(Duration::from_millis(250), Easing::EaseOut)
+                }),
                 container(text("h").size(20))
                     .width(50)
                     .height(50)
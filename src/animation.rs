@@ -0,0 +1,120 @@
+use iced_core::Color;
+use iced_core::time::{Duration, Instant};
+
+/// The curve an animated [`Blur`] follows between its old and new parameters.
+///
+/// [`Blur`]: crate::Blur
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Easing {
+    /// Moves at a constant speed.
+    Linear,
+    /// Starts slowly and speeds up, following a cubic curve.
+    EaseIn,
+    /// Starts quickly and slows down, following a cubic curve.
+    EaseOut,
+    /// Starts and ends slowly, speeding up in the middle.
+    #[default]
+    EaseInOut,
+}
+
+impl Easing {
+    /// Maps the progress `t` of an animation, clamped between 0 and 1, to the
+    /// eased progress between 0 and 1.
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t * t,
+            Easing::EaseOut => 1.0 - (1.0 - t).powi(3),
+            Easing::EaseInOut => {
+                if t < 0.5 {
+                    4.0 * t * t * t
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
+                }
+            }
+        }
+    }
+}
+
+pub(crate) trait Interpolate {
+    fn interpolate(&self, other: &Self, t: f32) -> Self;
+}
+
+impl Interpolate for f32 {
+    fn interpolate(&self, other: &Self, t: f32) -> Self {
+        self + (other - self) * t
+    }
+}
+
+impl Interpolate for Color {
+    fn interpolate(&self, other: &Self, t: f32) -> Self {
+        Color {
+            r: self.r.interpolate(&other.r, t),
+            g: self.g.interpolate(&other.g, t),
+            b: self.b.interpolate(&other.b, t),
+            a: self.a.interpolate(&other.a, t),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Animation {
+    pub duration: Duration,
+    pub easing: Easing,
+}
+
+impl Animation {
+    pub const INSTANT: Self = Self {
+        duration: Duration::ZERO,
+        easing: Easing::Linear,
+    };
+
+    pub fn is_instant(&self) -> bool {
+        self.duration.is_zero()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Transition<T> {
+    from: T,
+    to: T,
+    start: Instant,
+    animation: Animation,
+}
+
+impl<T: Interpolate + Clone> Transition<T> {
+    pub fn new(from: T, to: T, start: Instant, animation: Animation) -> Self {
+        Self {
+            from,
+            to,
+            start,
+            animation,
+        }
+    }
+
+    pub fn to(&self) -> &T {
+        &self.to
+    }
+
+    pub fn value(&self, now: Instant) -> T {
+        let t = self.animation.easing.apply(self.progress(now));
+
+        self.from.interpolate(&self.to, t)
+    }
+
+    pub fn is_animating(&self, now: Instant) -> bool {
+        self.progress(now) < 1.0
+    }
+
+    fn progress(&self, now: Instant) -> f32 {
+        if self.animation.is_instant() {
+            return 1.0;
+        }
+
+        let elapsed = now.saturating_duration_since(self.start);
+
+        (elapsed.as_secs_f32() / self.animation.duration.as_secs_f32()).min(1.0)
+    }
+}
@@ -0,0 +1,1021 @@
+use crate::animation::{Animation, Easing, Interpolate, Transition};
+use crate::mode::{self, Mode};
+use crate::shader;
+use crate::transparency;
+
+use iced_core::time::{Duration, Instant};
+use iced_core::widget::tree::{self, Tree};
+use iced_core::{
+    Border, Clipboard, Color, Element, Event, Gradient, Layout, Length, Padding, Rectangle, Shadow,
+    Shell, Size, Theme, Widget,
+};
+use iced_core::{layout, mouse, renderer, touch, window};
+use iced_widget::renderer::wgpu::primitive;
+
+use std::cell::{Cell, RefCell};
+use std::sync::Arc;
+
+pub fn blur<'a, Message, Theme>(radius: u32) -> Blur<'a, Message, Theme>
+where
+    Theme: Catalog,
+{
+    Blur::new(radius)
+}
+
+pub struct Blur<'a, Message, Theme = iced_core::Theme>
+where
+    Theme: Catalog,
+{
+    radius: Option<u32>,
+    opacity: f32,
+    blend_mode: BlendMode,
+    border: Option<Border>,
+    border_gradient: Option<Gradient>,
+    inner_shadow: Shadow,
+    highlight: Color,
+    highlight_width: f32,
+    refraction: f32,
+    displacement: Displacement,
+    refract_backdrop: bool,
+    edge_mode: EdgeMode,
+    precision: Precision,
+    backend: Backend,
+    batch: bool,
+    power_policy: PowerPolicy,
+    reduce_transparency: Option<bool>,
+    width: Length,
+    height: Length,
+    outset: Padding,
+    animation: Animation,
+    interaction: Interaction,
+    on_press: Option<Box<dyn Fn() -> Message + 'a>>,
+    on_luminance: Option<Box<dyn Fn(f32) -> Message + 'a>>,
+    class: Theme::Class<'a>,
+}
+
+impl<'a, Message, Theme> Blur<'a, Message, Theme>
+where
+    Theme: Catalog,
+{
+    // the least opacity of the fill drawn in place of a blur, high enough for
+    // the content over it to stand out, without hiding what is behind it
+    // entirely
+    const FALLBACK_ALPHA: f32 = 0.85;
+
+    pub fn new(radius: u32) -> Self {
+        Self {
+            radius: Some(radius),
+            ..Self::default()
+        }
+    }
+
+    /// Sets the blur radius, overriding the radius of the [`Style`].
+    pub fn radius(self, radius: u32) -> Self {
+        Self {
+            radius: Some(radius),
+            ..self
+        }
+    }
+
+    pub fn width(self, width: Length) -> Self {
+        Self { width, ..self }
+    }
+
+    pub fn height(self, height: Length) -> Self {
+        Self { height, ..self }
+    }
+
+    /// Sets the opacity of the blurred result over the original content.
+    pub fn opacity(self, opacity: f32) -> Self {
+        Self {
+            opacity: opacity.clamp(0.0, 1.0),
+            ..self
+        }
+    }
+
+    /// Sets the [`BlendMode`] used to composite the blurred result over the
+    /// original content.
+    pub fn blend_mode(self, blend_mode: BlendMode) -> Self {
+        Self { blend_mode, ..self }
+    }
+
+    /// Grows the blurred region past the layout bounds of the [`Blur`] by the
+    /// given amount on each side, or shrinks it within them when negative.
+    ///
+    /// A small outset hides the seams where a blurred bar meets the content
+    /// around it.
+    pub fn outset(self, outset: impl Into<Padding>) -> Self {
+        Self {
+            outset: outset.into(),
+            ..self
+        }
+    }
+
+    /// Sets the [`Border`] stroked around the blurred region, overriding the
+    /// border of the [`Style`].
+    pub fn border(self, border: impl Into<Border>) -> Self {
+        Self {
+            border: Some(border.into()),
+            ..self
+        }
+    }
+
+    /// Strokes the border with a [`Gradient`] instead of its color, like the
+    /// thin highlight along the edge of a glass panel.
+    pub fn border_gradient(self, gradient: impl Into<Gradient>) -> Self {
+        Self {
+            border_gradient: Some(gradient.into()),
+            ..self
+        }
+    }
+
+    /// Sets the [`Shadow`] cast inwards from the edges of the blurred region.
+    pub fn inner_shadow(self, inner_shadow: Shadow) -> Self {
+        Self {
+            inner_shadow,
+            ..self
+        }
+    }
+
+    /// Sets a specular highlight of the given `width` along the edges facing
+    /// the top left, like light caught by the rim of a glass panel.
+    pub fn highlight(self, color: impl Into<Color>, width: f32) -> Self {
+        Self {
+            highlight: color.into(),
+            highlight_width: width,
+            ..self
+        }
+    }
+
+    /// Bends the content showing through the region by up to `refraction`
+    /// logical pixels, following its [`Displacement`].
+    pub fn refraction(self, refraction: f32) -> Self {
+        Self {
+            refraction: refraction.max(0.0),
+            ..self
+        }
+    }
+
+    /// Sets the [`Displacement`] that bends the region by the
+    /// [`Blur::refraction`].
+    pub fn displacement(self, displacement: Displacement) -> Self {
+        Self {
+            displacement,
+            ..self
+        }
+    }
+
+    /// Refracts the unblurred content instead of the blurred result, like a
+    /// clear lens.
+    pub fn refract_backdrop(self, refract_backdrop: bool) -> Self {
+        Self {
+            refract_backdrop,
+            ..self
+        }
+    }
+
+    /// Sets how the [`Blur`] fills in the content past the edges of the window.
+    pub fn edge_mode(self, edge_mode: EdgeMode) -> Self {
+        Self { edge_mode, ..self }
+    }
+
+    /// Sets the [`Precision`] the [`Blur`] is computed in.
+    pub fn precision(self, precision: Precision) -> Self {
+        Self { precision, ..self }
+    }
+
+    /// Sets the [`Backend`] the [`Blur`] is computed with.
+    pub fn backend(self, backend: Backend) -> Self {
+        Self { backend, ..self }
+    }
+
+    /// Blurs the [`Blur`] together with the other batched blurs of the
+    /// frame, sharing a single pass per level between all of them.
+    ///
+    /// The backdrop of every batched blur is captured when the first of them
+    /// is drawn, so they should sit in the same layer and not overlap.
+    pub fn batch(self, batch: bool) -> Self {
+        Self { batch, ..self }
+    }
+
+    /// Sets the [`PowerPolicy`] of the [`Blur`], which can save the GPU time
+    /// spent blurring a mostly static backdrop.
+    pub fn power_policy(self, power_policy: PowerPolicy) -> Self {
+        Self {
+            power_policy,
+            ..self
+        }
+    }
+
+    /// Replaces the [`Blur`] with a mostly opaque fill of its tint, overriding
+    /// the global [`set_reduce_transparency`] setting.
+    ///
+    /// [`set_reduce_transparency`]: crate::set_reduce_transparency
+    pub fn reduce_transparency(self, reduce: bool) -> Self {
+        Self {
+            reduce_transparency: Some(reduce),
+            ..self
+        }
+    }
+
+    /// Animates any change to the parameters of the [`Blur`], including its
+    /// first appearance, over the given `duration`.
+    pub fn animate(self, duration: Duration, easing: Easing) -> Self {
+        Self {
+            animation: Animation { duration, easing },
+            ..self
+        }
+    }
+
+    /// Sets whether the [`Blur`] lets mouse and touch events through to the
+    /// widgets underneath.
+    pub fn interaction(self, interaction: Interaction) -> Self {
+        Self {
+            interaction,
+            ..self
+        }
+    }
+
+    /// Sets the message produced when the [`Blur`] is pressed, like
+    /// dismissing the modal it darkens the background of.
+    pub fn on_press(self, message: Message) -> Self
+    where
+        Message: Clone + 'a,
+    {
+        Self {
+            on_press: Some(Box::new(move || message.clone())),
+            ..self
+        }
+    }
+
+    /// Sets the message produced with the average relative luminance of the
+    /// blurred region, whenever it changes.
+    ///
+    /// The luminance is read back from the GPU a couple of frames after the
+    /// region is drawn.
+    pub fn on_luminance(self, on_luminance: impl Fn(f32) -> Message + 'a) -> Self {
+        Self {
+            on_luminance: Some(Box::new(on_luminance)),
+            ..self
+        }
+    }
+
+    /// Sets the style of the [`Blur`].
+    pub fn style(self, style: impl Fn(&Theme) -> Style + 'a) -> Self
+    where
+        Theme::Class<'a>: From<StyleFn<'a, Theme>>,
+    {
+        Self {
+            class: (Box::new(style) as StyleFn<'a, Theme>).into(),
+            ..self
+        }
+    }
+
+    /// Sets the style class of the [`Blur`].
+    pub fn class(self, class: impl Into<Theme::Class<'a>>) -> Self {
+        Self {
+            class: class.into(),
+            ..self
+        }
+    }
+
+    fn params(&self, style: &Style) -> Params {
+        Params {
+            radius: self.radius.map_or(style.radius, |radius| radius as f32),
+            opacity: self.opacity,
+            tint: style.tint,
+        }
+    }
+}
+
+impl<Message, Theme> Default for Blur<'_, Message, Theme>
+where
+    Theme: Catalog,
+{
+    fn default() -> Self {
+        Self {
+            radius: None,
+            opacity: 1.0,
+            blend_mode: BlendMode::default(),
+            border: None,
+            border_gradient: None,
+            inner_shadow: Shadow::default(),
+            highlight: Color::TRANSPARENT,
+            highlight_width: 0.0,
+            refraction: 0.0,
+            displacement: Displacement::default(),
+            refract_backdrop: false,
+            edge_mode: EdgeMode::default(),
+            precision: Precision::default(),
+            backend: Backend::default(),
+            batch: false,
+            power_policy: PowerPolicy::default(),
+            reduce_transparency: None,
+            width: Length::Fixed(100.0),
+            height: Length::Fixed(100.0),
+            outset: Padding::ZERO,
+            animation: Animation::INSTANT,
+            interaction: Interaction::default(),
+            on_press: None,
+            on_luminance: None,
+            class: Theme::default(),
+        }
+    }
+}
+
+/// How a [`Blur`] fills in the content it cannot capture, past the edges of
+/// the window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EdgeMode {
+    /// Repeats the pixels along the edge.
+    #[default]
+    Clamp,
+    /// Reflects the content back from the edge.
+    Mirror,
+    /// Treats everything past the edge as transparent, darkening the blur
+    /// towards it.
+    Transparent,
+}
+
+/// The precision of the textures a [`Blur`] is computed in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Precision {
+    /// Blurs in the format of the window surface.
+    #[default]
+    Standard,
+    /// Blurs in linear 16-bit floating point, which keeps bright highlights
+    /// from darkening and preserves the range of HDR surfaces.
+    High,
+}
+
+/// How the GPU computes the passes of a [`Blur`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Backend {
+    /// Draws every pass of the blur in a render pass of its own.
+    #[default]
+    Render,
+    /// Dispatches compute shaders that share the texels of their kernels
+    /// within a workgroup, which saves the setup of a render pass per pass.
+    ///
+    /// Falls back to [`Backend::Render`] on devices without compute shaders.
+    Compute,
+}
+
+/// How often a [`Blur`] captures and blurs its backdrop again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PowerPolicy {
+    /// Blurs the backdrop every frame.
+    #[default]
+    Live,
+    /// Blurs the backdrop at most `fps` times per second, compositing the
+    /// last blurred backdrop in between.
+    Throttled {
+        /// The most times per second the backdrop is blurred.
+        fps: u32,
+    },
+    /// Blurs the backdrop once, compositing it from then on.
+    ///
+    /// The backdrop is still blurred again whenever the [`Blur`] moves or
+    /// changes size.
+    Frozen,
+}
+
+/// How a [`Blur`] handles the mouse and touch events over it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Interaction {
+    /// Events reach the widgets underneath, as if the [`Blur`] was not there.
+    #[default]
+    PassThrough,
+    /// Events are captured, so the widgets underneath never see them.
+    Block,
+}
+
+/// How the blurred result of a [`Blur`] is combined with the content under
+/// it, following the blend modes of the W3C compositing specification.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BlendMode {
+    /// Replaces the content with the blurred result.
+    #[default]
+    Normal,
+    /// Multiplies the colors, which only ever darkens the content.
+    Multiply,
+    /// Multiplies the inverted colors, which only ever lightens the content.
+    Screen,
+    /// Multiplies the dark parts of the content and screens its light parts.
+    Overlay,
+    /// Darkens or lightens the content depending on the blurred result, like
+    /// a softer [`BlendMode::Overlay`].
+    SoftLight,
+    /// Keeps the hue and saturation of the content with the luminance of the
+    /// blurred result.
+    Luminosity,
+}
+
+/// The offsets that bend the content showing through a [`Blur`].
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum Displacement {
+    /// Bends the content inwards near the rounded edges of the region.
+    #[default]
+    Edge,
+    /// Offsets the content by a [`NormalMap`] stretched over the region.
+    NormalMap(NormalMap),
+}
+
+/// An RGBA normal map, with the horizontal and vertical components of each
+/// normal stored in its red and green channels.
+#[derive(Clone)]
+pub struct NormalMap {
+    id: shader::Id,
+    width: u32,
+    height: u32,
+    pixels: Arc<[u8]>,
+}
+
+impl NormalMap {
+    /// Creates a [`NormalMap`] from its RGBA `pixels`, row by row.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `pixels` does not hold exactly 4 bytes for each of the
+    /// `width * height` pixels.
+    pub fn from_rgba(width: u32, height: u32, pixels: impl Into<Vec<u8>>) -> Self {
+        let pixels = pixels.into();
+
+        assert_eq!(
+            pixels.len(),
+            width as usize * height as usize * 4,
+            "normal map must have 4 bytes per pixel"
+        );
+
+        Self {
+            id: shader::Id::unique(),
+            width,
+            height,
+            pixels: pixels.into(),
+        }
+    }
+
+    pub(crate) fn id(&self) -> shader::Id {
+        self.id
+    }
+
+    pub(crate) fn size(&self) -> Size<u32> {
+        Size::new(self.width, self.height)
+    }
+
+    pub(crate) fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+}
+
+impl PartialEq for NormalMap {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl std::fmt::Debug for NormalMap {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NormalMap")
+            .field("width", &self.width)
+            .field("height", &self.height)
+            .finish_non_exhaustive()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Params {
+    radius: f32,
+    opacity: f32,
+    tint: Color,
+}
+
+impl Params {
+    fn hidden(self) -> Self {
+        Self {
+            radius: 0.0,
+            opacity: 0.0,
+            ..self
+        }
+    }
+}
+
+impl Interpolate for Params {
+    fn interpolate(&self, other: &Self, t: f32) -> Self {
+        Self {
+            radius: self.radius.interpolate(&other.radius, t),
+            opacity: self.opacity.interpolate(&other.opacity, t),
+            tint: self.tint.interpolate(&other.tint, t),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct State {
+    id: shader::Id,
+    now: Option<Instant>,
+    // the style is only known while drawing, so the transition is started
+    // there for styles that change without an update
+    style: Cell<Option<Style>>,
+    transition: RefCell<Option<Transition<Params>>>,
+    luminance: Arc<shader::Luminance>,
+    sampling: Sampling,
+    last_luminance: Option<f32>,
+    mode: mode::Watch,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum Sampling {
+    #[default]
+    Idle,
+    Requested,
+    Awaiting(u32),
+}
+
+impl Sampling {
+    const MAX_FRAMES: u32 = 4;
+}
+
+impl State {
+    const LUMINANCE_EPSILON: f32 = 0.01;
+
+    fn tick(&mut self, now: Instant, target: Option<Params>, animation: Animation) -> bool {
+        self.now = Some(now);
+
+        // not drawn yet, the transition starts on the first draw
+        let Some(target) = target else {
+            return !animation.is_instant();
+        };
+
+        let transition = self.transition.get_mut();
+
+        if let Some(current) = transition.as_ref()
+            && *current.to() == target
+        {
+            return current.is_animating(now);
+        }
+
+        let from = transition
+            .as_ref()
+            .map_or(target.hidden(), |transition| transition.value(now));
+
+        let new = Transition::new(from, target, now, animation);
+        let is_animating = new.is_animating(now);
+
+        *transition = Some(new);
+
+        is_animating
+    }
+
+    // samples the luminance on the next frame and keeps redrawing until the
+    // result arrives, without sampling again on the frames it requested
+    fn sample(&mut self) -> (Option<f32>, bool) {
+        if let Some(luminance) = self.luminance.take() {
+            self.sampling = Sampling::Idle;
+
+            if self
+                .last_luminance
+                .is_some_and(|last| (last - luminance).abs() < Self::LUMINANCE_EPSILON)
+            {
+                return (None, false);
+            }
+
+            self.last_luminance = Some(luminance);
+
+            return (Some(luminance), false);
+        }
+
+        match self.sampling {
+            Sampling::Idle => {
+                self.sampling = Sampling::Requested;
+
+                (None, true)
+            }
+            Sampling::Requested => {
+                self.sampling = Sampling::Awaiting(1);
+
+                (None, true)
+            }
+            Sampling::Awaiting(frames) if frames < Sampling::MAX_FRAMES => {
+                self.sampling = Sampling::Awaiting(frames + 1);
+
+                (None, true)
+            }
+            Sampling::Awaiting(_) => {
+                self.sampling = Sampling::Idle;
+
+                (None, false)
+            }
+        }
+    }
+
+    fn params(&self, target: Params, animation: Animation) -> Params {
+        if animation.is_instant() {
+            return target;
+        }
+
+        let Some(now) = self.now else {
+            return target.hidden();
+        };
+
+        let mut transition = self.transition.borrow_mut();
+
+        match transition.as_ref() {
+            Some(transition) if *transition.to() == target => transition.value(now),
+            // the style changed since the last update, apply it at once
+            Some(_) => {
+                *transition = Some(Transition::new(target, target, now, Animation::INSTANT));
+
+                target
+            }
+            None => {
+                *transition = Some(Transition::new(target.hidden(), target, now, animation));
+
+                target.hidden()
+            }
+        }
+    }
+}
+
+impl<Message, Theme, Renderer> Widget<Message, Theme, Renderer> for Blur<'_, Message, Theme>
+where
+    Theme: Catalog,
+    Renderer: iced_core::Renderer + primitive::Renderer,
+{
+    fn tag(&self) -> tree::Tag {
+        tree::Tag::of::<State>()
+    }
+
+    fn state(&self) -> tree::State {
+        tree::State::new(State::default())
+    }
+
+    fn size(&self) -> Size<Length> {
+        Size::new(self.width, self.height)
+    }
+
+    fn layout(
+        &self,
+        _tree: &mut Tree,
+        _renderer: &Renderer,
+        limits: &layout::Limits,
+    ) -> layout::Node {
+        layout::atomic(limits, self.width, self.height)
+    }
+
+    fn update(
+        &mut self,
+        tree: &mut Tree,
+        event: &Event,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        _renderer: &Renderer,
+        _clipboard: &mut dyn Clipboard,
+        shell: &mut Shell<'_, Message>,
+        _viewport: &Rectangle,
+    ) {
+        if tree.state.downcast_mut::<State>().mode.changed() {
+            shell.request_redraw();
+        }
+
+        let now = match event {
+            Event::Window(window::Event::RedrawRequested(now)) => now,
+            Event::Mouse(_) | Event::Touch(_) => {
+                if !cursor.is_over(layout.bounds()) {
+                    return;
+                }
+
+                if let Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left))
+                | Event::Touch(touch::Event::FingerPressed { .. }) = event
+                    && let Some(on_press) = &self.on_press
+                {
+                    shell.publish(on_press());
+                }
+
+                if self.interaction == Interaction::Block {
+                    shell.capture_event();
+                }
+
+                return;
+            }
+            _ => return,
+        };
+
+        let state = tree.state.downcast_mut::<State>();
+        let target = state.style.get().map(|style| self.params(&style));
+
+        let mut request_redraw = state.tick(*now, target, self.animation);
+
+        if let Some(on_luminance) = &self.on_luminance {
+            let (luminance, sampling) = state.sample();
+
+            if let Some(luminance) = luminance {
+                shell.publish(on_luminance(luminance));
+            }
+
+            request_redraw |= sampling;
+        }
+
+        if request_redraw {
+            shell.request_redraw();
+        }
+    }
+
+    fn mouse_interaction(
+        &self,
+        _tree: &Tree,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        _viewport: &Rectangle,
+        _renderer: &Renderer,
+    ) -> mouse::Interaction {
+        if !cursor.is_over(layout.bounds()) {
+            return mouse::Interaction::None;
+        }
+
+        match self.interaction {
+            Interaction::PassThrough => mouse::Interaction::None,
+            Interaction::Block => mouse::Interaction::Idle,
+        }
+    }
+
+    fn draw(
+        &self,
+        tree: &Tree,
+        renderer: &mut Renderer,
+        theme: &Theme,
+        _style: &renderer::Style,
+        layout: Layout<'_>,
+        _cursor: mouse::Cursor,
+        _viewport: &Rectangle,
+    ) {
+        let state = tree.state.downcast_ref::<State>();
+        let style = theme.style(&self.class);
+        let params = state.params(self.params(&style), self.animation);
+
+        state.style.set(Some(style));
+
+        let border = self.border.unwrap_or(style.border);
+        let bounds = layout.bounds();
+        let region = Rectangle {
+            x: bounds.x - self.outset.left,
+            y: bounds.y - self.outset.top,
+            width: (bounds.width + self.outset.horizontal()).max(0.0),
+            height: (bounds.height + self.outset.vertical()).max(0.0),
+        };
+
+        let reduce_transparency = self
+            .reduce_transparency
+            .unwrap_or_else(transparency::reduce_transparency);
+
+        // the backdrop is hidden behind a mostly opaque fill of the tint
+        // instead, when transparency is reduced or the backdrop cannot be
+        // blurred
+        if reduce_transparency || mode::mode() == Some(Mode::Fallback) {
+            renderer.fill_quad(
+                renderer::Quad {
+                    bounds: region,
+                    border,
+                    ..renderer::Quad::default()
+                },
+                Color {
+                    a: params.tint.a.max(Self::FALLBACK_ALPHA) * params.opacity,
+                    ..params.tint
+                },
+            );
+
+            return;
+        }
+
+        let mut primitive =
+            shader::Primitive::new(state.id, params.radius, params.opacity, self.blend_mode)
+                .tint(params.tint)
+                .noise(style.noise)
+                .border(border)
+                .border_gradient(self.border_gradient.or(style.border_gradient))
+                .inner_shadow(self.inner_shadow)
+                .highlight(self.highlight, self.highlight_width)
+                .refraction(self.refraction)
+                .displacement(self.displacement.clone(), self.refract_backdrop)
+                .edge_mode(self.edge_mode)
+                .precision(self.precision)
+                .backend(self.backend)
+                .batch(self.batch)
+                .power_policy(self.power_policy);
+
+        if self.on_luminance.is_some() && state.sampling == Sampling::Requested {
+            primitive = primitive.sample_luminance(state.luminance.clone());
+        }
+
+        renderer.draw_primitive(region, primitive);
+    }
+}
+
+impl<'a, Message, Theme, Renderer> From<Blur<'a, Message, Theme>>
+    for Element<'a, Message, Theme, Renderer>
+where
+    Message: 'a,
+    Theme: Catalog + 'a,
+    Renderer: 'a,
+    Renderer: iced_core::Renderer + primitive::Renderer,
+{
+    fn from(blur: Blur<'a, Message, Theme>) -> Self {
+        Element::new(blur)
+    }
+}
+
+/// The appearance of a [`Blur`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Style {
+    /// The color mixed over the blurred result.
+    pub tint: Color,
+    /// The blur radius, unless set on the [`Blur`] itself.
+    pub radius: f32,
+    /// The amount of grain added to the blurred result.
+    pub noise: f32,
+    /// The [`Border`] of the blurred region, including its corner radius.
+    pub border: Border,
+    /// The [`Gradient`] used to stroke the border instead of its color.
+    pub border_gradient: Option<Gradient>,
+}
+
+/// The theme catalog of a [`Blur`].
+pub trait Catalog {
+    /// The item class of the [`Catalog`].
+    type Class<'a>;
+
+    /// The default class produced by the [`Catalog`].
+    fn default<'a>() -> Self::Class<'a>;
+
+    /// The [`Style`] of a class.
+    fn style(&self, class: &Self::Class<'_>) -> Style;
+}
+
+/// A styling function for a [`Blur`].
+pub type StyleFn<'a, Theme> = Box<dyn Fn(&Theme) -> Style + 'a>;
+
+impl Catalog for Theme {
+    type Class<'a> = StyleFn<'a, Self>;
+
+    fn default<'a>() -> Self::Class<'a> {
+        Box::new(default)
+    }
+
+    fn style(&self, class: &Self::Class<'_>) -> Style {
+        class(self)
+    }
+}
+
+/// The default style of a [`Blur`], a plain blur without tint or noise.
+///
+/// Use [`frosted`], [`light`] or [`dark`] for a tinted, grainy material.
+pub fn default(theme: &Theme) -> Style {
+    Style {
+        // invisible, but keeps the color of the background for the solid
+        // fills drawn in place of the blur
+        tint: theme
+            .extended_palette()
+            .background
+            .base
+            .color
+            .scale_alpha(0.0),
+        radius: 6.0,
+        noise: 0.0,
+        border: Border::default(),
+        border_gradient: None,
+    }
+}
+
+/// A frosted style following the lightness of the theme, either [`light`] or
+/// [`dark`].
+pub fn frosted(theme: &Theme) -> Style {
+    if theme.extended_palette().is_dark {
+        dark(theme)
+    } else {
+        light(theme)
+    }
+}
+
+/// A light, frosted style.
+pub fn light(theme: &Theme) -> Style {
+    let palette = theme.extended_palette();
+
+    Style {
+        tint: palette.background.base.color.scale_alpha(0.3),
+        radius: 6.0,
+        noise: 0.02,
+        border: Border::default(),
+        border_gradient: None,
+    }
+}
+
+/// A dark, smoky style.
+pub fn dark(theme: &Theme) -> Style {
+    let palette = theme.extended_palette();
+
+    Style {
+        tint: palette.background.base.color.scale_alpha(0.45),
+        radius: 6.0,
+        noise: 0.03,
+        border: Border::default(),
+        border_gradient: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FADE: Animation = Animation {
+        duration: Duration::from_millis(200),
+        easing: Easing::Linear,
+    };
+
+    const PARAMS: Params = Params {
+        radius: 10.0,
+        opacity: 1.0,
+        tint: Color::TRANSPARENT,
+    };
+
+    #[test]
+    fn sampling_waits_for_the_result() {
+        let mut state = State::default();
+
+        assert_eq!(state.sample(), (None, true));
+        assert_eq!(state.sampling, Sampling::Requested);
+
+        assert_eq!(state.sample(), (None, true));
+        assert_eq!(state.sampling, Sampling::Awaiting(1));
+
+        state.luminance.set(0.5);
+
+        assert_eq!(state.sample(), (Some(0.5), false));
+        assert_eq!(state.sampling, Sampling::Idle);
+        assert_eq!(state.last_luminance, Some(0.5));
+    }
+
+    #[test]
+    fn sampling_gives_up_after_max_frames() {
+        let mut state = State::default();
+
+        assert_eq!(state.sample(), (None, true));
+        assert_eq!(state.sample(), (None, true));
+
+        for frames in 1..Sampling::MAX_FRAMES {
+            assert_eq!(state.sampling, Sampling::Awaiting(frames));
+            assert_eq!(state.sample(), (None, true));
+        }
+
+        assert_eq!(state.sampling, Sampling::Awaiting(Sampling::MAX_FRAMES));
+        assert_eq!(state.sample(), (None, false));
+        assert_eq!(state.sampling, Sampling::Idle);
+
+        // the next frame samples again
+        assert_eq!(state.sample(), (None, true));
+        assert_eq!(state.sampling, Sampling::Requested);
+    }
+
+    #[test]
+    fn sampling_ignores_small_changes() {
+        let mut state = State {
+            last_luminance: Some(0.5),
+            sampling: Sampling::Awaiting(1),
+            ..State::default()
+        };
+
+        state.luminance.set(0.5 + State::LUMINANCE_EPSILON / 2.0);
+
+        assert_eq!(state.sample(), (None, false));
+        assert_eq!(state.sampling, Sampling::Idle);
+        assert_eq!(state.last_luminance, Some(0.5));
+
+        state.luminance.set(0.5 + State::LUMINANCE_EPSILON * 2.0);
+
+        assert_eq!(
+            state.sample(),
+            (Some(0.5 + State::LUMINANCE_EPSILON * 2.0), false)
+        );
+        assert_eq!(
+            state.last_luminance,
+            Some(0.5 + State::LUMINANCE_EPSILON * 2.0)
+        );
+    }
+
+    #[test]
+    fn tick_animates_until_the_transition_ends() {
+        let mut state = State::default();
+        let now = Instant::now();
+
+        // nothing was drawn yet
+        assert!(state.tick(now, None, FADE));
+        assert!(!state.tick(now, None, Animation::INSTANT));
+
+        assert!(state.tick(now, Some(PARAMS), FADE));
+        assert_eq!(state.params(PARAMS, FADE), PARAMS.hidden());
+
+        // the same target keeps the transition going
+        assert!(state.tick(now + Duration::from_millis(100), Some(PARAMS), FADE));
+        assert!(!state.tick(now + Duration::from_millis(200), Some(PARAMS), FADE));
+        assert_eq!(state.params(PARAMS, FADE), PARAMS);
+    }
+}
@@ -0,0 +1,55 @@
+use crate::mode::{self, Mode};
+
+use iced_widget::renderer::wgpu::wgpu;
+
+use std::fmt;
+use std::sync::Mutex;
+
+static LAST_ERROR: Mutex<Option<Error>> = Mutex::new(None);
+
+/// An error the GPU raised while preparing the blurs.
+///
+/// Once an error is raised, the blurs of the renderer are drawn in
+/// [`Mode::Fallback`] instead of crashing the application.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// The GPU ran out of memory.
+    OutOfMemory,
+    /// A resource was created or used with invalid parameters.
+    Validation(String),
+    /// The driver failed internally.
+    Internal(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::OutOfMemory => write!(f, "out of memory"),
+            Self::Validation(description) => write!(f, "validation error: {description}"),
+            Self::Internal(description) => write!(f, "internal error: {description}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<wgpu::Error> for Error {
+    fn from(error: wgpu::Error) -> Self {
+        match error {
+            wgpu::Error::OutOfMemory { .. } => Self::OutOfMemory,
+            wgpu::Error::Validation { description, .. } => Self::Validation(description),
+            wgpu::Error::Internal { description, .. } => Self::Internal(description),
+        }
+    }
+}
+
+/// Returns the last [`Error`] the GPU raised while preparing the blurs.
+pub fn last_error() -> Option<Error> {
+    LAST_ERROR.lock().unwrap().clone()
+}
+
+pub(crate) fn report(error: Error) {
+    mode::degrade(Mode::Fallback);
+
+    *LAST_ERROR.lock().unwrap() = Some(error);
+}
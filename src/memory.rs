@@ -0,0 +1,62 @@
+use std::sync::atomic::{self, AtomicU64};
+
+static IN_USE: AtomicU64 = AtomicU64::new(0);
+static POOLED: AtomicU64 = AtomicU64::new(0);
+static BUDGET: AtomicU64 = AtomicU64::new(MemoryUsage::DEFAULT_BUDGET);
+
+/// The GPU memory taken by the textures of every blur in the application.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryUsage {
+    /// The bytes of the textures currently used to blur.
+    pub in_use: u64,
+    /// The bytes of the textures kept around to be reused.
+    pub pooled: u64,
+    /// The bytes the pooled textures are evicted to stay under.
+    pub budget: u64,
+}
+
+impl MemoryUsage {
+    /// The default memory budget, 128 MiB.
+    pub const DEFAULT_BUDGET: u64 = 128 * 1024 * 1024;
+
+    /// The total bytes of the textures in use and pooled.
+    pub fn total(&self) -> u64 {
+        self.in_use + self.pooled
+    }
+}
+
+/// Returns the current [`MemoryUsage`] of the blurs.
+pub fn memory_usage() -> MemoryUsage {
+    MemoryUsage {
+        in_use: IN_USE.load(atomic::Ordering::Relaxed),
+        pooled: POOLED.load(atomic::Ordering::Relaxed),
+        budget: BUDGET.load(atomic::Ordering::Relaxed),
+    }
+}
+
+/// Sets the memory budget of the blurs, in bytes.
+///
+/// Textures that are not in use are evicted, least recently used first, until
+/// the total usage fits the budget. Textures in use are never evicted, so the
+/// budget may be exceeded while large regions are blurred.
+pub fn set_memory_budget(bytes: u64) {
+    BUDGET.store(bytes, atomic::Ordering::Relaxed);
+}
+
+pub(crate) fn allocate(bytes: u64) {
+    IN_USE.fetch_add(bytes, atomic::Ordering::Relaxed);
+}
+
+pub(crate) fn free(bytes: u64) {
+    IN_USE.fetch_sub(bytes, atomic::Ordering::Relaxed);
+}
+
+pub(crate) fn pool(bytes: u64) {
+    IN_USE.fetch_sub(bytes, atomic::Ordering::Relaxed);
+    POOLED.fetch_add(bytes, atomic::Ordering::Relaxed);
+}
+
+pub(crate) fn unpool(bytes: u64) {
+    POOLED.fetch_sub(bytes, atomic::Ordering::Relaxed);
+    IN_USE.fetch_add(bytes, atomic::Ordering::Relaxed);
+}
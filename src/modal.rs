@@ -0,0 +1,371 @@
+use crate::Easing;
+use crate::blur::{self, Blur, Interaction};
+
+use iced_core::keyboard::{self, key};
+use iced_core::time::Duration;
+use iced_core::widget::operation::{self, Operation, focusable};
+use iced_core::widget::tree::Tree;
+use iced_core::{
+    Clipboard, Color, Element, Event, Layout, Length, Rectangle, Shell, Size, Vector, Widget,
+};
+use iced_core::{layout, mouse, overlay, renderer};
+use iced_widget::renderer::wgpu::primitive;
+use iced_widget::{center, container, opaque};
+
+pub fn modal<'a, Message, Theme, Renderer>(
+    base: impl Into<Element<'a, Message, Theme, Renderer>>,
+    dialog: impl Into<Element<'a, Message, Theme, Renderer>>,
+    on_dismiss: Message,
+) -> Modal<'a, Message, Theme, Renderer>
+where
+    Message: Clone + 'a,
+    Theme: blur::Catalog + container::Catalog + 'a,
+    Theme::Class<'a>: From<blur::StyleFn<'a, Theme>>,
+    Renderer: iced_core::Renderer + primitive::Renderer + 'a,
+{
+    Modal::new(base, dialog, on_dismiss)
+}
+
+/// Shows a dialog centered over its base content, behind a dimmed [`Blur`].
+///
+/// The dialog is dismissed with Escape or a click outside of it, and the base
+/// content receives no input while it is shown. Tab cycles the focus within
+/// the dialog.
+pub struct Modal<'a, Message, Theme, Renderer> {
+    base: Element<'a, Message, Theme, Renderer>,
+    backdrop: Element<'a, Message, Theme, Renderer>,
+    dialog: Element<'a, Message, Theme, Renderer>,
+    on_dismiss: Message,
+}
+
+impl<'a, Message, Theme, Renderer> Modal<'a, Message, Theme, Renderer>
+where
+    Message: Clone + 'a,
+    Theme: blur::Catalog + container::Catalog + 'a,
+    Theme::Class<'a>: From<blur::StyleFn<'a, Theme>>,
+    Renderer: iced_core::Renderer + primitive::Renderer + 'a,
+{
+    pub fn new(
+        base: impl Into<Element<'a, Message, Theme, Renderer>>,
+        dialog: impl Into<Element<'a, Message, Theme, Renderer>>,
+        on_dismiss: Message,
+    ) -> Self {
+        let backdrop: Blur<'a, Message, Theme> = Blur::default()
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .interaction(Interaction::Block)
+            .on_press(on_dismiss.clone())
+            .animate(Duration::from_millis(200), Easing::EaseOut)
+            .style(dimmed);
+
+        Self {
+            base: base.into(),
+            backdrop: backdrop.into(),
+            dialog: center(opaque(dialog)).into(),
+            on_dismiss,
+        }
+    }
+}
+
+// the default style of the theme, darkened to push the base content back
+fn dimmed<Theme: blur::Catalog>(theme: &Theme) -> blur::Style {
+    blur::Style {
+        tint: Color::BLACK.scale_alpha(0.4),
+        ..theme.style(&<Theme as blur::Catalog>::default())
+    }
+}
+
+impl<Message, Theme, Renderer> Modal<'_, Message, Theme, Renderer>
+where
+    Renderer: iced_core::Renderer,
+{
+    // moves the focus within the dialog, wrapping around its ends
+    fn cycle_focus(
+        &self,
+        tree: &mut Tree,
+        layout: Layout<'_>,
+        renderer: &Renderer,
+        backwards: bool,
+    ) {
+        let mut operation: Box<dyn Operation> = if backwards {
+            Box::new(focusable::focus_previous())
+        } else {
+            Box::new(focusable::focus_next())
+        };
+
+        loop {
+            self.dialog
+                .as_widget()
+                .operate(tree, layout, renderer, operation.as_mut());
+
+            match operation.finish() {
+                operation::Outcome::Chain(next) => operation = next,
+                _ => break,
+            }
+        }
+    }
+}
+
+impl<Message, Theme, Renderer> Widget<Message, Theme, Renderer>
+    for Modal<'_, Message, Theme, Renderer>
+where
+    Message: Clone,
+    Renderer: iced_core::Renderer,
+{
+    fn children(&self) -> Vec<Tree> {
+        vec![
+            Tree::new(&self.base),
+            Tree::new(&self.backdrop),
+            Tree::new(&self.dialog),
+        ]
+    }
+
+    fn diff(&self, tree: &mut Tree) {
+        tree.diff_children(&[&self.base, &self.backdrop, &self.dialog]);
+    }
+
+    fn size(&self) -> Size<Length> {
+        self.base.as_widget().size()
+    }
+
+    fn layout(
+        &self,
+        tree: &mut Tree,
+        renderer: &Renderer,
+        limits: &layout::Limits,
+    ) -> layout::Node {
+        let base = self
+            .base
+            .as_widget()
+            .layout(&mut tree.children[0], renderer, limits);
+
+        let size = base.size();
+        let limits = layout::Limits::new(Size::ZERO, size);
+
+        let backdrop = self
+            .backdrop
+            .as_widget()
+            .layout(&mut tree.children[1], renderer, &limits);
+        let dialog = self
+            .dialog
+            .as_widget()
+            .layout(&mut tree.children[2], renderer, &limits);
+
+        layout::Node::with_children(size, vec![base, backdrop, dialog])
+    }
+
+    fn operate(
+        &self,
+        tree: &mut Tree,
+        layout: Layout<'_>,
+        renderer: &Renderer,
+        operation: &mut dyn Operation,
+    ) {
+        // the base content is out of reach while the dialog is shown
+        let layout = layout.children().nth(2).unwrap();
+
+        self.dialog
+            .as_widget()
+            .operate(&mut tree.children[2], layout, renderer, operation);
+    }
+
+    fn update(
+        &mut self,
+        tree: &mut Tree,
+        event: &Event,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        renderer: &Renderer,
+        clipboard: &mut dyn Clipboard,
+        shell: &mut Shell<'_, Message>,
+        viewport: &Rectangle,
+    ) {
+        let mut children = layout.children();
+        let base_layout = children.next().unwrap();
+        let backdrop_layout = children.next().unwrap();
+        let dialog_layout = children.next().unwrap();
+
+        self.dialog.as_widget_mut().update(
+            &mut tree.children[2],
+            event,
+            dialog_layout,
+            cursor,
+            renderer,
+            clipboard,
+            shell,
+            viewport,
+        );
+
+        if shell.is_event_captured() {
+            return;
+        }
+
+        if let Event::Keyboard(keyboard::Event::KeyPressed {
+            key: keyboard::Key::Named(named),
+            modifiers,
+            ..
+        }) = event
+        {
+            match named {
+                key::Named::Escape => {
+                    shell.publish(self.on_dismiss.clone());
+                    shell.capture_event();
+                    return;
+                }
+                key::Named::Tab => {
+                    self.cycle_focus(
+                        &mut tree.children[2],
+                        dialog_layout,
+                        renderer,
+                        modifiers.shift(),
+                    );
+                    shell.capture_event();
+                    shell.request_redraw();
+                    return;
+                }
+                _ => {}
+            }
+        }
+
+        self.backdrop.as_widget_mut().update(
+            &mut tree.children[1],
+            event,
+            backdrop_layout,
+            cursor,
+            renderer,
+            clipboard,
+            shell,
+            viewport,
+        );
+
+        // keeps the base content animating without letting any input through
+        if let Event::Window(_) = event {
+            self.base.as_widget_mut().update(
+                &mut tree.children[0],
+                event,
+                base_layout,
+                mouse::Cursor::Unavailable,
+                renderer,
+                clipboard,
+                shell,
+                viewport,
+            );
+        }
+    }
+
+    fn mouse_interaction(
+        &self,
+        tree: &Tree,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        viewport: &Rectangle,
+        renderer: &Renderer,
+    ) -> mouse::Interaction {
+        let mut children = layout.children().skip(1);
+        let backdrop_layout = children.next().unwrap();
+        let dialog_layout = children.next().unwrap();
+
+        let interaction = self.dialog.as_widget().mouse_interaction(
+            &tree.children[2],
+            dialog_layout,
+            cursor,
+            viewport,
+            renderer,
+        );
+
+        if interaction != mouse::Interaction::None {
+            return interaction;
+        }
+
+        self.backdrop.as_widget().mouse_interaction(
+            &tree.children[1],
+            backdrop_layout,
+            cursor,
+            viewport,
+            renderer,
+        )
+    }
+
+    fn draw(
+        &self,
+        tree: &Tree,
+        renderer: &mut Renderer,
+        theme: &Theme,
+        style: &renderer::Style,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        viewport: &Rectangle,
+    ) {
+        let mut children = layout.children();
+        let base_layout = children.next().unwrap();
+        let backdrop_layout = children.next().unwrap();
+        let dialog_layout = children.next().unwrap();
+
+        self.base.as_widget().draw(
+            &tree.children[0],
+            renderer,
+            theme,
+            style,
+            base_layout,
+            mouse::Cursor::Unavailable,
+            viewport,
+        );
+
+        // each layer is drawn over the last, so the backdrop blurs the base
+        // content and the dialog stays sharp
+        renderer.with_layer(*viewport, |renderer| {
+            self.backdrop.as_widget().draw(
+                &tree.children[1],
+                renderer,
+                theme,
+                style,
+                backdrop_layout,
+                cursor,
+                viewport,
+            );
+        });
+
+        renderer.with_layer(*viewport, |renderer| {
+            self.dialog.as_widget().draw(
+                &tree.children[2],
+                renderer,
+                theme,
+                style,
+                dialog_layout,
+                cursor,
+                viewport,
+            );
+        });
+    }
+
+    fn overlay<'b>(
+        &'b mut self,
+        tree: &'b mut Tree,
+        layout: Layout<'_>,
+        renderer: &Renderer,
+        viewport: &Rectangle,
+        translation: Vector,
+    ) -> Option<overlay::Element<'b, Message, Theme, Renderer>> {
+        let layout = layout.children().nth(2).unwrap();
+
+        self.dialog.as_widget_mut().overlay(
+            &mut tree.children[2],
+            layout,
+            renderer,
+            viewport,
+            translation,
+        )
+    }
+}
+
+impl<'a, Message, Theme, Renderer> From<Modal<'a, Message, Theme, Renderer>>
+    for Element<'a, Message, Theme, Renderer>
+where
+    Message: Clone + 'a,
+    Theme: 'a,
+    Renderer: iced_core::Renderer + 'a,
+{
+    fn from(modal: Modal<'a, Message, Theme, Renderer>) -> Self {
+        Element::new(modal)
+    }
+}
@@ -0,0 +1,66 @@
+use std::sync::atomic::{self, AtomicU8};
+
+// the mode only ever degrades, so the order of the values matters
+static MODE: AtomicU8 = AtomicU8::new(0);
+
+/// How the blurs are drawn, depending on what the device supports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    /// Every feature of the blurs is available.
+    Full,
+    /// The device cannot render to or filter 16-bit float textures, so
+    /// blurs ignore [`Precision::High`] and [`Backend::Compute`].
+    ///
+    /// [`Precision::High`]: crate::Precision::High
+    /// [`Backend::Compute`]: crate::Backend::Compute
+    Reduced,
+    /// The framebuffer cannot be copied or sampled, or the GPU raised an
+    /// [`Error`], so blurs are drawn as a translucent fill of their tint
+    /// instead.
+    ///
+    /// [`Error`]: crate::Error
+    Fallback,
+}
+
+/// Returns the [`Mode`] the blurs are drawn in, once the device was probed
+/// when the first blur was drawn.
+pub fn mode() -> Option<Mode> {
+    match MODE.load(atomic::Ordering::Relaxed) {
+        1 => Some(Mode::Full),
+        2 => Some(Mode::Reduced),
+        3 => Some(Mode::Fallback),
+        _ => None,
+    }
+}
+
+// degrades the mode, keeping a worse one that was already found
+pub(crate) fn degrade(mode: Mode) {
+    let value = match mode {
+        Mode::Full => 1,
+        Mode::Reduced => 2,
+        Mode::Fallback => 3,
+    };
+
+    MODE.fetch_max(value, atomic::Ordering::Relaxed);
+}
+
+// follows the mode for a widget, which needs to be redrawn once the mode
+// degrades, as that happens while rendering, after the widget was drawn
+//
+// the first check counts as a change, since the device is only probed once
+// the first blur is rendered
+#[derive(Debug, Default)]
+pub(crate) struct Watch {
+    seen: Option<Option<Mode>>,
+}
+
+impl Watch {
+    pub fn changed(&mut self) -> bool {
+        let current = Some(mode());
+        let changed = self.seen != current;
+
+        self.seen = current;
+
+        changed
+    }
+}
@@ -0,0 +1,454 @@
+use crate::blur::{self, Blur};
+
+use iced_core::widget::Operation;
+use iced_core::widget::tree::{self, Tree};
+use iced_core::{
+    Clipboard, Element, Event, Layout, Length, Rectangle, Shell, Size, Vector, Widget,
+};
+use iced_core::{layout, mouse, overlay, renderer};
+use iced_widget::renderer::wgpu::primitive;
+
+pub fn blur_overlays<'a, Message, Theme, Renderer>(
+    content: impl Into<Element<'a, Message, Theme, Renderer>>,
+) -> BlurOverlays<'a, Message, Theme, Renderer>
+where
+    Message: 'a,
+    Theme: blur::Catalog + 'a,
+    Renderer: iced_core::Renderer + primitive::Renderer + 'a,
+{
+    BlurOverlays::new(content)
+}
+
+/// Puts the overlays of its content, like menus, dropdowns and tooltips, over
+/// a blurred backdrop of whatever they cover.
+///
+/// The overlays still draw their own background, which should be translucent
+/// for the blur to show through.
+///
+/// The overlays of several widgets are grouped together into one covering the
+/// whole window, which is blurred under each of them instead. A single overlay
+/// covering the window, like the modals and sheets of other crates, cannot be
+/// told apart from such a group, so it needs [`BlurOverlays::whole`] to be
+/// blurred as one.
+pub struct BlurOverlays<'a, Message, Theme, Renderer> {
+    content: Element<'a, Message, Theme, Renderer>,
+    blur: Element<'a, Message, Theme, Renderer>,
+    whole: bool,
+}
+
+impl<'a, Message, Theme, Renderer> BlurOverlays<'a, Message, Theme, Renderer>
+where
+    Message: 'a,
+    Theme: blur::Catalog + 'a,
+    Renderer: iced_core::Renderer + primitive::Renderer + 'a,
+{
+    pub fn new(content: impl Into<Element<'a, Message, Theme, Renderer>>) -> Self {
+        Self {
+            content: content.into(),
+            blur: Blur::default().into(),
+            whole: false,
+        }
+    }
+
+    /// Sets the [`Blur`] drawn under every overlay, which is sized to the
+    /// bounds of each overlay, even when several are open at once.
+    pub fn blur(self, blur: Blur<'a, Message, Theme>) -> Self {
+        Self {
+            blur: blur.into(),
+            ..self
+        }
+    }
+
+    /// Blurs under the overlay of the content as a whole, instead of under
+    /// each of the overlays it groups.
+    pub fn whole(self) -> Self {
+        Self {
+            whole: true,
+            ..self
+        }
+    }
+}
+
+impl<Message, Theme, Renderer> Widget<Message, Theme, Renderer>
+    for BlurOverlays<'_, Message, Theme, Renderer>
+where
+    Renderer: iced_core::Renderer,
+{
+    fn tag(&self) -> tree::Tag {
+        tree::Tag::of::<State>()
+    }
+
+    fn state(&self) -> tree::State {
+        tree::State::new(State {
+            blurs: vec![Tree::new(self.blur.as_widget())],
+            viewport: Size::ZERO,
+            whole: self.whole,
+        })
+    }
+
+    fn children(&self) -> Vec<Tree> {
+        vec![Tree::new(&self.content)]
+    }
+
+    fn diff(&self, tree: &mut Tree) {
+        tree.diff_children(std::slice::from_ref(&self.content));
+
+        let state = tree.state.downcast_mut::<State>();
+
+        for blur in &mut state.blurs {
+            blur.diff(self.blur.as_widget());
+        }
+    }
+
+    fn size(&self) -> Size<Length> {
+        self.content.as_widget().size()
+    }
+
+    fn layout(
+        &self,
+        tree: &mut Tree,
+        renderer: &Renderer,
+        limits: &layout::Limits,
+    ) -> layout::Node {
+        self.content
+            .as_widget()
+            .layout(&mut tree.children[0], renderer, limits)
+    }
+
+    fn operate(
+        &self,
+        tree: &mut Tree,
+        layout: Layout<'_>,
+        renderer: &Renderer,
+        operation: &mut dyn Operation,
+    ) {
+        self.content
+            .as_widget()
+            .operate(&mut tree.children[0], layout, renderer, operation);
+    }
+
+    fn update(
+        &mut self,
+        tree: &mut Tree,
+        event: &Event,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        renderer: &Renderer,
+        clipboard: &mut dyn Clipboard,
+        shell: &mut Shell<'_, Message>,
+        viewport: &Rectangle,
+    ) {
+        self.content.as_widget_mut().update(
+            &mut tree.children[0],
+            event,
+            layout,
+            cursor,
+            renderer,
+            clipboard,
+            shell,
+            viewport,
+        );
+    }
+
+    fn mouse_interaction(
+        &self,
+        tree: &Tree,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        viewport: &Rectangle,
+        renderer: &Renderer,
+    ) -> mouse::Interaction {
+        self.content.as_widget().mouse_interaction(
+            &tree.children[0],
+            layout,
+            cursor,
+            viewport,
+            renderer,
+        )
+    }
+
+    fn draw(
+        &self,
+        tree: &Tree,
+        renderer: &mut Renderer,
+        theme: &Theme,
+        style: &renderer::Style,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        viewport: &Rectangle,
+    ) {
+        self.content.as_widget().draw(
+            &tree.children[0],
+            renderer,
+            theme,
+            style,
+            layout,
+            cursor,
+            viewport,
+        );
+    }
+
+    fn overlay<'b>(
+        &'b mut self,
+        tree: &'b mut Tree,
+        layout: Layout<'_>,
+        renderer: &Renderer,
+        viewport: &Rectangle,
+        translation: Vector,
+    ) -> Option<overlay::Element<'b, Message, Theme, Renderer>> {
+        let state = tree.state.downcast_mut::<State>();
+        state.whole = self.whole;
+
+        let content = self.content.as_widget_mut().overlay(
+            &mut tree.children[0],
+            layout,
+            renderer,
+            viewport,
+            translation,
+        )?;
+
+        Some(overlay::Element::new(Box::new(Backdrop {
+            content,
+            blur: &mut self.blur,
+            state,
+        })))
+    }
+}
+
+impl<'a, Message, Theme, Renderer> From<BlurOverlays<'a, Message, Theme, Renderer>>
+    for Element<'a, Message, Theme, Renderer>
+where
+    Message: 'a,
+    Theme: 'a,
+    Renderer: iced_core::Renderer + 'a,
+{
+    fn from(overlays: BlurOverlays<'a, Message, Theme, Renderer>) -> Self {
+        Element::new(overlays)
+    }
+}
+
+// a tree for the blur under each overlay, the size of the viewport they
+// were laid out in, and whether the overlay is blurred as a whole
+struct State {
+    blurs: Vec<Tree>,
+    viewport: Size,
+    whole: bool,
+}
+
+impl State {
+    // the bounds of every overlay in `layout`, looking through the groups
+    // `overlay::from_children` makes of the overlays of several children,
+    // which cover the whole viewport
+    fn overlays(&self, layout: Layout<'_>, bounds: &mut Vec<Rectangle>) {
+        let is_group = !self.whole
+            && layout.bounds() == Rectangle::with_size(self.viewport)
+            && layout.children().next().is_some();
+
+        if is_group {
+            for child in layout.children() {
+                self.overlays(child, bounds);
+            }
+        } else {
+            bounds.push(layout.bounds());
+        }
+    }
+}
+
+// overlays drawn over a blur of their own bounds
+struct Backdrop<'a, 'b, Message, Theme, Renderer> {
+    content: overlay::Element<'a, Message, Theme, Renderer>,
+    blur: &'a mut Element<'b, Message, Theme, Renderer>,
+    state: &'a mut State,
+}
+
+impl<Message, Theme, Renderer> overlay::Overlay<Message, Theme, Renderer>
+    for Backdrop<'_, '_, Message, Theme, Renderer>
+where
+    Renderer: iced_core::Renderer,
+{
+    fn layout(&mut self, renderer: &Renderer, bounds: Size) -> layout::Node {
+        let node = self.content.as_overlay_mut().layout(renderer, bounds);
+
+        self.state.viewport = bounds;
+
+        // every overlay needs a blur with a state of its own
+        let mut overlays = Vec::new();
+        self.state.overlays(Layout::new(&node), &mut overlays);
+
+        while self.state.blurs.len() < overlays.len() {
+            self.state.blurs.push(Tree::new(self.blur.as_widget()));
+        }
+
+        node
+    }
+
+    fn draw(
+        &self,
+        renderer: &mut Renderer,
+        theme: &Theme,
+        style: &renderer::Style,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+    ) {
+        let mut overlays = Vec::new();
+        self.state.overlays(layout, &mut overlays);
+
+        // the blurs copy the framebuffer when their layer is rendered, so they
+        // need layers of their own under the overlays
+        for (bounds, tree) in overlays.into_iter().zip(&self.state.blurs) {
+            let node = layout::Node::new(bounds.size()).move_to(bounds.position());
+
+            renderer.with_layer(bounds, |renderer| {
+                self.blur.as_widget().draw(
+                    tree,
+                    renderer,
+                    theme,
+                    style,
+                    Layout::new(&node),
+                    cursor,
+                    &bounds,
+                );
+            });
+        }
+
+        renderer.with_layer(layout.bounds(), |renderer| {
+            self.content
+                .as_overlay()
+                .draw(renderer, theme, style, layout, cursor);
+        });
+    }
+
+    fn operate(&mut self, layout: Layout<'_>, renderer: &Renderer, operation: &mut dyn Operation) {
+        self.content
+            .as_overlay_mut()
+            .operate(layout, renderer, operation);
+    }
+
+    fn update(
+        &mut self,
+        event: &Event,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        renderer: &Renderer,
+        clipboard: &mut dyn Clipboard,
+        shell: &mut Shell<'_, Message>,
+    ) {
+        self.content
+            .as_overlay_mut()
+            .update(event, layout, cursor, renderer, clipboard, shell);
+
+        // keeps the blurs animating, without letting them handle any input
+        if let Event::Window(_) = event {
+            let mut overlays = Vec::new();
+            self.state.overlays(layout, &mut overlays);
+
+            for (bounds, tree) in overlays.into_iter().zip(&mut self.state.blurs) {
+                let node = layout::Node::new(bounds.size()).move_to(bounds.position());
+
+                self.blur.as_widget_mut().update(
+                    tree,
+                    event,
+                    Layout::new(&node),
+                    cursor,
+                    renderer,
+                    clipboard,
+                    shell,
+                    &bounds,
+                );
+            }
+        }
+    }
+
+    fn mouse_interaction(
+        &self,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        renderer: &Renderer,
+    ) -> mouse::Interaction {
+        self.content
+            .as_overlay()
+            .mouse_interaction(layout, cursor, renderer)
+    }
+
+    fn overlay<'c>(
+        &'c mut self,
+        layout: Layout<'_>,
+        renderer: &Renderer,
+    ) -> Option<overlay::Element<'c, Message, Theme, Renderer>> {
+        self.content.as_overlay_mut().overlay(layout, renderer)
+    }
+
+    fn index(&self) -> f32 {
+        self.content.as_overlay().index()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use iced_core::Point;
+
+    const VIEWPORT: Size = Size::new(800.0, 600.0);
+
+    fn state(whole: bool) -> State {
+        State {
+            blurs: Vec::new(),
+            viewport: VIEWPORT,
+            whole,
+        }
+    }
+
+    fn overlay(x: f32, y: f32, width: f32, height: f32) -> layout::Node {
+        layout::Node::new(Size::new(width, height)).move_to(Point::new(x, y))
+    }
+
+    fn overlays(state: &State, node: &layout::Node) -> Vec<Rectangle> {
+        let mut bounds = Vec::new();
+        state.overlays(Layout::new(node), &mut bounds);
+
+        bounds
+    }
+
+    #[test]
+    fn single_overlay() {
+        let node = overlay(10.0, 20.0, 100.0, 200.0);
+
+        assert_eq!(
+            overlays(&state(false), &node),
+            [Rectangle::new(
+                Point::new(10.0, 20.0),
+                Size::new(100.0, 200.0)
+            )]
+        );
+    }
+
+    #[test]
+    fn group_of_overlays() {
+        let node = layout::Node::with_children(
+            VIEWPORT,
+            vec![
+                overlay(10.0, 20.0, 100.0, 200.0),
+                layout::Node::with_children(VIEWPORT, vec![overlay(300.0, 40.0, 50.0, 60.0)]),
+            ],
+        );
+
+        assert_eq!(
+            overlays(&state(false), &node),
+            [
+                Rectangle::new(Point::new(10.0, 20.0), Size::new(100.0, 200.0)),
+                Rectangle::new(Point::new(300.0, 40.0), Size::new(50.0, 60.0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn whole_overlay_covering_the_viewport() {
+        let node = layout::Node::with_children(VIEWPORT, vec![overlay(200.0, 150.0, 400.0, 300.0)]);
+
+        assert_eq!(
+            overlays(&state(true), &node),
+            [Rectangle::with_size(VIEWPORT)]
+        );
+    }
+}
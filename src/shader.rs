@@ -1,49 +1,453 @@
+pub mod vibrant;
+
+mod compute;
+mod profiler;
+
+use crate::blur::{Backend, BlendMode, Displacement, EdgeMode, NormalMap, PowerPolicy, Precision};
+use crate::error::{self, Error};
+use crate::memory;
+use crate::mode::{self, Mode};
+
 use iced_core::Rectangle;
 use iced_core::Size;
-use iced_core::mouse;
+use iced_core::time::{Duration, Instant};
+use iced_core::{Border, Color, Gradient, Shadow};
 use iced_widget::renderer::wgpu::wgpu;
 use iced_widget::renderer::wgpu::wgpu::util::DeviceExt;
 use iced_widget::shader;
 
+use compute::Compute;
+use profiler::{Profiler, Stage};
+
+use std::collections::{HashMap, HashSet};
+use std::pin::pin;
+use std::sync::atomic::{self, AtomicBool, AtomicU64};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Id(u64);
+
+impl Id {
+    pub fn unique() -> Self {
+        static NEXT: AtomicU64 = AtomicU64::new(0);
+
+        Self(NEXT.fetch_add(1, atomic::Ordering::Relaxed))
+    }
+}
+
+impl Default for Id {
+    fn default() -> Self {
+        Self::unique()
+    }
+}
+
+// the average luminance of a blurred region, read back from the GPU
+#[derive(Debug, Default)]
+pub struct Luminance(Mutex<Option<f32>>);
+
+impl Luminance {
+    pub fn take(&self) -> Option<f32> {
+        self.0.lock().unwrap().take()
+    }
+
+    pub fn set(&self, value: f32) {
+        *self.0.lock().unwrap() = Some(value);
+    }
+}
+
 #[derive(Debug)]
-pub struct Shader(u32);
+pub struct Primitive {
+    id: Id,
+    radius: f32,
+    opacity: f32,
+    blend_mode: BlendMode,
+    tint: Color,
+    noise: f32,
+    border: Border,
+    border_gradient: Option<Gradient>,
+    inner_shadow: Shadow,
+    highlight: Color,
+    highlight_width: f32,
+    refraction: f32,
+    displacement: Displacement,
+    refract_backdrop: bool,
+    edge_mode: EdgeMode,
+    precision: Precision,
+    backend: Backend,
+    batch: bool,
+    power_policy: PowerPolicy,
+    luminance: Option<Arc<Luminance>>,
+}
 
-impl Shader {
+impl Primitive {
     const MAX_BLUR_RADIUS: u32 = 16;
-    pub fn new(radius: u32) -> Self {
-        Self(radius)
+    const MAX_MARGIN: u32 = 256;
+
+    pub fn new(id: Id, radius: f32, opacity: f32, blend_mode: BlendMode) -> Self {
+        Self {
+            id,
+            radius: radius.clamp(0.0, Self::MAX_BLUR_RADIUS as f32),
+            opacity: opacity.clamp(0.0, 1.0),
+            blend_mode,
+            tint: Color::TRANSPARENT,
+            noise: 0.0,
+            border: Border::default(),
+            border_gradient: None,
+            inner_shadow: Shadow::default(),
+            highlight: Color::TRANSPARENT,
+            highlight_width: 0.0,
+            refraction: 0.0,
+            displacement: Displacement::Edge,
+            refract_backdrop: false,
+            edge_mode: EdgeMode::Clamp,
+            precision: Precision::Standard,
+            backend: Backend::Render,
+            batch: false,
+            power_policy: PowerPolicy::Live,
+            luminance: None,
+        }
+    }
+
+    pub fn tint(self, tint: Color) -> Self {
+        Self { tint, ..self }
+    }
+
+    pub fn noise(self, noise: f32) -> Self {
+        Self { noise, ..self }
+    }
+
+    pub fn border(self, border: Border) -> Self {
+        Self { border, ..self }
+    }
+
+    pub fn border_gradient(self, border_gradient: Option<Gradient>) -> Self {
+        Self {
+            border_gradient,
+            ..self
+        }
+    }
+
+    pub fn inner_shadow(self, inner_shadow: Shadow) -> Self {
+        Self {
+            inner_shadow,
+            ..self
+        }
+    }
+
+    pub fn highlight(self, highlight: Color, highlight_width: f32) -> Self {
+        Self {
+            highlight,
+            highlight_width,
+            ..self
+        }
+    }
+
+    pub fn refraction(self, refraction: f32) -> Self {
+        Self { refraction, ..self }
+    }
+
+    pub fn displacement(self, displacement: Displacement, refract_backdrop: bool) -> Self {
+        Self {
+            displacement,
+            refract_backdrop,
+            ..self
+        }
+    }
+
+    pub fn edge_mode(self, edge_mode: EdgeMode) -> Self {
+        Self { edge_mode, ..self }
+    }
+
+    pub fn precision(self, precision: Precision) -> Self {
+        Self { precision, ..self }
+    }
+
+    pub fn backend(self, backend: Backend) -> Self {
+        Self { backend, ..self }
+    }
+
+    pub fn batch(self, batch: bool) -> Self {
+        Self { batch, ..self }
+    }
+
+    pub fn power_policy(self, power_policy: PowerPolicy) -> Self {
+        Self {
+            power_policy,
+            ..self
+        }
+    }
+
+    pub fn sample_luminance(self, luminance: Arc<Luminance>) -> Self {
+        Self {
+            luminance: Some(luminance),
+            ..self
+        }
+    }
+
+    fn passes(&self) -> u32 {
+        (self.radius.ceil() as u32).clamp(1, Self::MAX_BLUR_RADIUS)
+    }
+
+    // fractional radii shrink the sample offsets of every pass, so that the
+    // blur grows continuously between whole pass counts
+    fn spread(&self) -> f32 {
+        if self.radius < 1.0 {
+            self.radius
+        } else {
+            2f32.powf(self.radius - self.passes() as f32)
+        }
     }
-}
 
-impl<Message> shader::Program<Message> for Shader {
-    type State = ();
-    type Primitive = Self;
+    // how far around the region the blur reaches for samples, so they are
+    // captured instead of clamped to the edge of the region
+    fn margin(&self) -> u32 {
+        let reach = 2f32.powi(self.passes() as i32) * self.spread();
 
-    fn draw(&self, _state: &Self::State, _cursor: mouse::Cursor, _bounds: Rectangle) -> Self {
-        Self(self.0)
+        (reach.ceil() as u32).min(Self::MAX_MARGIN)
+    }
+
+    // the start and length of the part of the framebuffer captured along one
+    // axis, for a region at `start` of `length` logical pixels
+    //
+    // the margin past the edges of the frame is only kept for the edge modes
+    // that fill it differently than the passes clamp to the capture, and the
+    // margin is trimmed first to fit the textures the device can create
+    fn capture_span(
+        &self,
+        start: f32,
+        length: f32,
+        scale_factor: f32,
+        frame: u32,
+        limit: u32,
+    ) -> (i64, u32) {
+        let margin = i64::from(self.margin());
+        let start = (start * scale_factor).round() as i64;
+        let end = start + (length * scale_factor).round() as i64;
+
+        let (mut low, mut high) = (start - margin, end + margin);
+
+        if self.edge_mode == EdgeMode::Clamp {
+            low = low.max(0);
+            high = high.min(i64::from(frame));
+        }
+
+        let excess = (high - low - i64::from(limit)).max(0);
+        let before = (excess / 2).min(start - low).max(0);
+
+        low += before;
+        high -= excess - before;
+
+        (low, (high - low).max(1) as u32)
+    }
+
+    // the precision and backend the blur is drawn with in `mode`
+    fn key(&self, mode: Mode) -> (Precision, Backend) {
+        match mode {
+            Mode::Full => (self.precision, self.backend),
+            Mode::Reduced | Mode::Fallback => (Precision::Standard, Backend::Render),
+        }
+    }
+
+    // prepares the blur within the error scopes of `prepare`
+    fn prepare_pipeline(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        format: wgpu::TextureFormat,
+        storage: &mut shader::Storage,
+        bounds: &Rectangle,
+        viewport: &shader::Viewport,
+    ) {
+        if !storage.has::<Pipelines>() {
+            storage.store(Pipelines::new(device, queue, format));
+        }
+
+        let Some(mode) = storage.get::<Pipelines>().map(|pipelines| pipelines.mode) else {
+            return;
+        };
+
+        if mode == Mode::Fallback {
+            return;
+        }
+
+        let (precision, backend) = self.key(mode);
+
+        let scale_factor = viewport.scale_factor() as f32;
+        let frame = viewport.physical_size();
+        let limit = device.limits().max_texture_dimension_2d;
+        let (x, width) =
+            self.capture_span(bounds.x, bounds.width, scale_factor, frame.width, limit);
+        let (y, height) =
+            self.capture_span(bounds.y, bounds.height, scale_factor, frame.height, limit);
+        let size = Size::new(width, height);
+
+        // 8-bit surfaces without an sRGB view store gamma encoded colors,
+        // which need to be linearized to blur in higher precision
+        let linearize = precision == Precision::High
+            && matches!(
+                format,
+                wgpu::TextureFormat::Rgba8Unorm | wgpu::TextureFormat::Bgra8Unorm
+            );
+        let color_format = if linearize {
+            format.add_srgb_suffix()
+        } else {
+            format
+        };
+
+        let region = Rectangle {
+            x: bounds.x * scale_factor,
+            y: bounds.y * scale_factor,
+            width: bounds.width * scale_factor,
+            height: bounds.height * scale_factor,
+        };
+
+        let mut gradient = [0.0; 4];
+        let mut stops = [[0.0; 4]; 8];
+        let mut offsets = [[0.0; 4]; 2];
+        let mut stop_count = 0;
+
+        if let Some(Gradient::Linear(linear)) = self.border_gradient {
+            let (start, end) = linear.angle.to_distance(&region);
+            gradient = [start.x, start.y, end.x, end.y];
+
+            for (i, stop) in linear.stops.iter().flatten().enumerate() {
+                stops[i] = pack(stop.color, color_format);
+                offsets[i / 4][i % 4] = stop.offset;
+                stop_count += 1;
+            }
+        }
+
+        let Some(Pipelines {
+            pipelines,
+            pool,
+            profiler,
+            ..
+        }) = storage.get_mut::<Pipelines>()
+        else {
+            return;
+        };
+
+        profiler.prepare(device);
+
+        let pipeline = pipelines.entry((precision, backend)).or_insert_with(|| {
+            Pipeline::new(
+                device,
+                queue,
+                size,
+                format,
+                intermediate_format(precision, backend, format),
+                backend,
+                profiler.clone(),
+            )
+        });
+
+        let mut capture = Capture::new([x, y], size, frame, [0, 0]);
+
+        pipeline.begin(device, pool, self.id);
+
+        // a reused blur is composited from the level it kept, outside of the
+        // atlas
+        let reused = pipeline.reuses(self.id, &capture, self.radius, self.power_policy);
+        let batch = self.batch && self.opacity > 0.0 && !reused;
+
+        if !reused {
+            capture.atlas = pipeline.allocate(device, pool, self.id, size, batch);
+        }
+
+        pipeline.prepare(
+            device,
+            queue,
+            self.id,
+            Region {
+                capture,
+                uniforms: Uniforms {
+                    tint: pack(self.tint, color_format),
+                    border_color: pack(self.border.color, color_format),
+                    border_radius: <[f32; 4]>::from(self.border.radius)
+                        .map(|radius| radius * scale_factor),
+                    origin: [region.x, region.y],
+                    size: [region.width, region.height],
+                    capture_origin: capture.texture_origin(),
+                    spread: self.spread(),
+                    opacity: self.opacity,
+                    blend_mode: self.blend_mode as u32,
+                    noise: self.noise,
+                    border_width: self.border.width * scale_factor,
+                    _pad0: 0.0,
+                    gradient,
+                    stops,
+                    offsets,
+                    stop_count,
+                    _pad1: 0,
+                    _pad2: 0,
+                    _pad3: 0,
+                    shadow_color: pack(self.inner_shadow.color, color_format),
+                    highlight_color: pack(self.highlight, color_format),
+                    shadow_offset: [
+                        self.inner_shadow.offset.x * scale_factor,
+                        self.inner_shadow.offset.y * scale_factor,
+                    ],
+                    shadow_blur: self.inner_shadow.blur_radius * scale_factor,
+                    refraction: self.refraction * scale_factor,
+                    highlight_width: self.highlight_width * scale_factor,
+                    displacement: match self.displacement {
+                        Displacement::Edge => 0,
+                        Displacement::NormalMap(_) => 1,
+                    },
+                    refract_backdrop: self.refract_backdrop as u32,
+                    edge_mode: self.edge_mode as u32,
+                    valid: capture.valid(),
+                    capture_size: [size.width as f32, size.height as f32],
+                    linearize: linearize as u32,
+                    // the levels keep the encoded colors of the surface
+                    // unless it has an sRGB view or they were linearized
+                    encoded: (!linearize && !format.is_srgb()) as u32,
+                    atlas: capture.atlas.map(|x| x as f32),
+                    _pad4: [0.0; 2],
+                },
+                normal_map: match &self.displacement {
+                    Displacement::Edge => None,
+                    Displacement::NormalMap(normal_map) => Some(normal_map),
+                },
+                luminance: self.luminance.clone(),
+                passes: self.passes(),
+                batch,
+            },
+        );
+
+        pipeline.freeze(
+            device,
+            pool,
+            self.id,
+            self.radius,
+            self.power_policy,
+            reused,
+        );
     }
 }
 
-impl shader::Primitive for Shader {
+impl shader::Primitive for Primitive {
     fn prepare(
         &self,
         device: &wgpu::Device,
-        _queue: &wgpu::Queue,
+        queue: &wgpu::Queue,
         format: wgpu::TextureFormat,
         storage: &mut shader::Storage,
         bounds: &Rectangle,
         viewport: &shader::Viewport,
     ) {
-        let size = Size::new(
-            (bounds.width * viewport.scale_factor() as f32).round() as u32,
-            (bounds.height * viewport.scale_factor() as f32).round() as u32,
-        );
-        if !storage.has::<Pipeline>() {
-            storage.store(Pipeline::new(device, size, format));
+        if storage.has::<Error>() {
+            return;
         }
 
-        let pipeline = storage.get_mut::<Pipeline>().unwrap();
-        pipeline.update(device, size);
+        let prepared = scope(device, || {
+            self.prepare_pipeline(device, queue, format, storage, bounds, viewport);
+        });
+
+        if let Err(error) = prepared {
+            fail(storage, error);
+        }
     }
 
     fn render(
@@ -53,29 +457,493 @@ impl shader::Primitive for Shader {
         target: &wgpu::TextureView,
         clip_bounds: &Rectangle<u32>,
     ) {
-        storage.get::<Pipeline>().unwrap().render(
-            encoder,
-            target.texture(),
-            target,
-            clip_bounds,
-            self.0,
+        if self.opacity <= 0.0 || storage.has::<Error>() {
+            return;
+        }
+
+        let Some(pipelines) = storage.get::<Pipelines>() else {
+            return;
+        };
+
+        if !can_copy(target) {
+            return;
+        }
+
+        let Some(pipeline) = pipelines.pipelines.get(&self.key(pipelines.mode)) else {
+            return;
+        };
+
+        pipeline.render(encoder, target.texture(), target, clip_bounds, self.id);
+    }
+}
+
+// the uniforms of a level of the blur chain, padded to the largest uniform
+// offset alignment a device may require
+#[repr(C, align(256))]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct Level {
+    size: u32,
+    _pad: [u32; 63],
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct Uniforms {
+    tint: [f32; 4],
+    border_color: [f32; 4],
+    border_radius: [f32; 4],
+    origin: [f32; 2],
+    size: [f32; 2],
+    capture_origin: [f32; 2],
+    spread: f32,
+    opacity: f32,
+    blend_mode: u32,
+    noise: f32,
+    border_width: f32,
+    _pad0: f32,
+    gradient: [f32; 4],
+    stops: [[f32; 4]; 8],
+    offsets: [[f32; 4]; 2],
+    stop_count: u32,
+    _pad1: u32,
+    _pad2: u32,
+    _pad3: u32,
+    shadow_color: [f32; 4],
+    highlight_color: [f32; 4],
+    shadow_offset: [f32; 2],
+    shadow_blur: f32,
+    refraction: f32,
+    highlight_width: f32,
+    displacement: u32,
+    refract_backdrop: u32,
+    edge_mode: u32,
+    valid: [f32; 4],
+    capture_size: [f32; 2],
+    linearize: u32,
+    encoded: u32,
+    atlas: [f32; 2],
+    _pad4: [f32; 2],
+}
+
+// the part of the framebuffer copied for a region and its margin, and where
+// it lands in the intermediate textures
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+struct Capture {
+    origin: [i64; 2],
+    size: Size<u32>,
+    source: Rectangle<u32>,
+    offset: [u32; 2],
+    atlas: [u32; 2],
+}
+
+impl Capture {
+    // `origin` is the position of the first texel in the framebuffer, which
+    // may lie outside of it, and `atlas` is where that texel is placed in
+    // the intermediate textures
+    fn new(origin: [i64; 2], size: Size<u32>, frame: Size<u32>, atlas: [u32; 2]) -> Self {
+        let left = origin[0].max(0);
+        let top = origin[1].max(0);
+        let right = (origin[0] + i64::from(size.width)).min(i64::from(frame.width));
+        let bottom = (origin[1] + i64::from(size.height)).min(i64::from(frame.height));
+
+        Self {
+            origin,
+            size,
+            source: Rectangle {
+                x: left as u32,
+                y: top as u32,
+                width: (right - left).max(0) as u32,
+                height: (bottom - top).max(0) as u32,
+            },
+            offset: [(left - origin[0]) as u32, (top - origin[1]) as u32],
+            atlas,
+        }
+    }
+
+    // the framebuffer position of the first texel of the intermediate
+    // textures
+    fn texture_origin(&self) -> [f32; 2] {
+        [
+            (self.origin[0] - i64::from(self.atlas[0])) as f32,
+            (self.origin[1] - i64::from(self.atlas[1])) as f32,
+        ]
+    }
+
+    fn set_viewport(&self, render_pass: &mut wgpu::RenderPass<'_>) {
+        render_pass.set_viewport(
+            self.atlas[0] as f32,
+            self.atlas[1] as f32,
+            self.size.width as f32,
+            self.size.height as f32,
+            0.0,
+            1.0,
+        );
+    }
+
+    // the copied texels, in pixels
+    fn valid(&self) -> [f32; 4] {
+        [
+            self.offset[0] as f32,
+            self.offset[1] as f32,
+            (self.offset[0] + self.source.width) as f32,
+            (self.offset[1] + self.source.height) as f32,
+        ]
+    }
+
+    fn is_empty(&self) -> bool {
+        self.source.width == 0 || self.source.height == 0
+    }
+
+    // whether both captures copy the same texels of the framebuffer, wherever
+    // they are placed
+    fn same_source(&self, other: &Capture) -> bool {
+        self.origin == other.origin && self.size == other.size && self.source == other.source
+    }
+}
+
+// packs the captures of a frame into rows, so that every batched region keeps
+// its levels until it is composited, even after other regions were blurred
+//
+// regions that are not batched are blurred and composited at once, so they
+// share a single slot, sized to the largest of them
+#[derive(Debug, Default)]
+struct Atlas {
+    cursor: [u32; 2],
+    row_height: u32,
+    extent: Size<u32>,
+    regions: HashSet<Id>,
+    shared: Option<([u32; 2], Size<u32>)>,
+    shared_size: Size<u32>,
+    largest: Size<u32>,
+}
+
+impl Atlas {
+    // starts a new frame, sizing the shared slot up front to what the last
+    // one needed
+    fn next(&self) -> Self {
+        Self {
+            shared_size: self.largest,
+            ..Self::default()
+        }
+    }
+
+    fn contains(&self, id: Id) -> bool {
+        self.regions.contains(&id)
+    }
+
+    // returns where a capture of `size` goes, and whether the atlas ran out
+    // of room and started over from the top
+    fn allocate(&mut self, id: Id, size: Size<u32>, batch: bool, limit: u32) -> ([u32; 2], bool) {
+        self.regions.insert(id);
+
+        if batch {
+            return self.place(size, limit);
+        }
+
+        self.largest = Size::new(
+            self.largest.width.max(size.width),
+            self.largest.height.max(size.height),
         );
+
+        let slot = self.shared.map_or(self.shared_size, |(_, slot)| slot);
+        let fits = size.width <= slot.width && size.height <= slot.height;
+
+        if let Some((position, _)) = self.shared
+            && fits
+        {
+            return (position, false);
+        }
+
+        // a larger region moves the shared slot, which leaves the old one
+        // unused for the rest of the frame
+        let size = Size::new(size.width.max(slot.width), size.height.max(slot.height));
+        let (position, wrapped) = self.place(size, limit);
+
+        self.shared = Some((position, size));
+
+        (position, wrapped)
+    }
+
+    fn place(&mut self, size: Size<u32>, limit: u32) -> ([u32; 2], bool) {
+        if self.cursor[0] + size.width > limit {
+            self.cursor = [0, self.cursor[1] + self.row_height];
+            self.row_height = 0;
+        }
+
+        let wrapped = self.cursor[1] + size.height > limit;
+
+        if wrapped {
+            self.cursor = [0, 0];
+            self.row_height = 0;
+        }
+
+        let position = self.cursor;
+
+        self.cursor[0] += size.width;
+        self.row_height = self.row_height.max(size.height);
+        self.extent = Size::new(
+            self.extent.width.max(position[0] + size.width),
+            self.extent.height.max(position[1] + size.height),
+        );
+
+        (position, wrapped)
+    }
+}
+
+// colors are stored linearly in sRGB textures
+pub fn pack(color: Color, format: wgpu::TextureFormat) -> [f32; 4] {
+    if format.is_srgb() {
+        color.into_linear()
+    } else {
+        [color.r, color.g, color.b, color.a]
+    }
+}
+
+// compute shaders can only write to the intermediate textures in a format
+// with guaranteed storage support
+fn intermediate_format(
+    precision: Precision,
+    backend: Backend,
+    format: wgpu::TextureFormat,
+) -> wgpu::TextureFormat {
+    match (precision, backend) {
+        (Precision::Standard, Backend::Render) => format,
+        (Precision::High, _) | (_, Backend::Compute) => wgpu::TextureFormat::Rgba16Float,
+    }
+}
+
+// rounds the size of the intermediate textures up to a multiple of `STEP`,
+// so that resizing the window swaps between a few sizes the pool can reuse
+fn bucket(size: Size<u32>, limit: u32) -> Size<u32> {
+    const STEP: u32 = 256;
+
+    let round = |length: u32| length.max(1).next_multiple_of(STEP).min(limit);
+
+    Size::new(round(size.width), round(size.height))
+}
+
+// finds the best mode the device can draw the blurs in, given the format of
+// the surface they are drawn on
+fn probe(device: &wgpu::Device, format: wgpu::TextureFormat) -> Mode {
+    let supports = |format: wgpu::TextureFormat| {
+        let features = format.guaranteed_format_features(device.features());
+
+        features
+            .allowed_usages
+            .contains(wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING)
+            && features
+                .flags
+                .contains(wgpu::TextureFormatFeatureFlags::FILTERABLE)
+    };
+
+    if !supports(format) {
+        Mode::Fallback
+    } else if !supports(wgpu::TextureFormat::Rgba16Float) {
+        Mode::Reduced
+    } else {
+        Mode::Full
+    }
+}
+
+// runs `f` within error scopes, returning the first error the GPU raised
+fn scope<T>(device: &wgpu::Device, f: impl FnOnce() -> T) -> Result<T, Error> {
+    const FILTERS: [wgpu::ErrorFilter; 3] = [
+        wgpu::ErrorFilter::Validation,
+        wgpu::ErrorFilter::OutOfMemory,
+        wgpu::ErrorFilter::Internal,
+    ];
+
+    for filter in FILTERS {
+        device.push_error_scope(filter);
+    }
+
+    let output = f();
+
+    // every scope must be popped, even after an error was found
+    let mut error = None;
+
+    for _ in FILTERS {
+        let mut scope = pin!(device.pop_error_scope());
+
+        // native devices report errors as soon as they are raised, while
+        // those on the web are only reported asynchronously and missed here
+        if let Poll::Ready(Some(raised)) =
+            scope.as_mut().poll(&mut Context::from_waker(Waker::noop()))
+        {
+            error = error.or(Some(Error::from(raised)));
+        }
+    }
+
+    match error {
+        Some(error) => Err(error),
+        None => Ok(output),
+    }
+}
+
+// stops drawing the blurs of a renderer once the GPU raised an error, drawing
+// them as solid fills instead
+fn fail(storage: &mut shader::Storage, error: Error) {
+    log::error!("iced_blur stopped blurring after a GPU error: {error}");
+
+    if let Some(pipelines) = storage.get_mut::<Pipelines>() {
+        pipelines.pipelines.clear();
+    }
+
+    error::report(error.clone());
+    storage.store(error);
+}
+
+// whether the backdrop can be copied out of `target`, falling back to solid
+// fills otherwise, as some surfaces are only known not to allow it once their
+// frame is rendered
+fn can_copy(target: &wgpu::TextureView) -> bool {
+    let copyable = target
+        .texture()
+        .usage()
+        .contains(wgpu::TextureUsages::COPY_SRC);
+
+    if !copyable {
+        mode::degrade(Mode::Fallback);
+    }
+
+    copyable
+}
+
+// a pipeline for each precision and backend in use, the textures they left
+// behind when they changed size, the profiler measuring all of them and the
+// mode the device supports
+struct Pipelines {
+    pipelines: HashMap<(Precision, Backend), Pipeline>,
+    pool: Pool,
+    profiler: Arc<Profiler>,
+    mode: Mode,
+}
+
+impl Pipelines {
+    fn new(device: &wgpu::Device, queue: &wgpu::Queue, format: wgpu::TextureFormat) -> Self {
+        let mode = probe(device, format);
+
+        mode::degrade(mode);
+
+        Self {
+            pipelines: HashMap::new(),
+            pool: Pool::default(),
+            profiler: Arc::new(Profiler::new(device, queue)),
+            mode,
+        }
+    }
+}
+
+// keeps the intermediate textures that are no longer in use, so that a
+// pipeline changing size reuses one of the same size instead of allocating
+// it, evicting the least recently released ones beyond the memory budget
+#[derive(Default)]
+struct Pool {
+    textures: Vec<(Texture, Instant)>,
+}
+
+impl Pool {
+    fn acquire(
+        &mut self,
+        device: &wgpu::Device,
+        size: Size<u32>,
+        format: wgpu::TextureFormat,
+        sampler: &wgpu::Sampler,
+    ) -> Texture {
+        let pooled = self
+            .textures
+            .iter()
+            .position(|(texture, _)| texture.size() == size && texture.texture.format() == format);
+
+        match pooled {
+            Some(i) => {
+                let (texture, _) = self.textures.swap_remove(i);
+                memory::unpool(texture.bytes());
+
+                texture
+            }
+            None => Texture::new(device, size, format, sampler),
+        }
+    }
+
+    fn release(&mut self, texture: Texture) {
+        memory::pool(texture.bytes());
+        self.textures.push((texture, Instant::now()));
+    }
+
+    fn trim(&mut self) {
+        loop {
+            let usage = memory::memory_usage();
+
+            if usage.total() <= usage.budget {
+                break;
+            }
+
+            let Some(oldest) = self
+                .textures
+                .iter()
+                .enumerate()
+                .min_by_key(|(_, (_, released))| *released)
+                .map(|(i, _)| i)
+            else {
+                break;
+            };
+
+            let (texture, _) = self.textures.swap_remove(oldest);
+            memory::unpool(texture.bytes());
+        }
+    }
+}
+
+impl Drop for Pool {
+    fn drop(&mut self) {
+        for (texture, _) in &self.textures {
+            memory::unpool(texture.bytes());
+        }
     }
 }
 
+// what a region needs to be blurred in a frame
+struct Region<'a> {
+    capture: Capture,
+    uniforms: Uniforms,
+    normal_map: Option<&'a NormalMap>,
+    luminance: Option<Arc<Luminance>>,
+    passes: u32,
+    batch: bool,
+}
+
 pub struct Pipeline {
-    offset_alignment: u32,
     downscale_pipeline: wgpu::RenderPipeline,
     upscale_pipeline: wgpu::RenderPipeline,
+    composite_pipeline: wgpu::RenderPipeline,
+    luminance_pipeline: wgpu::RenderPipeline,
+    luminance_texture: wgpu::Texture,
+    luminance_view: wgpu::TextureView,
     texel_bind_group: wgpu::BindGroup,
+    instance_layout: wgpu::BindGroupLayout,
+    instances: HashMap<Id, Instance>,
+    flat_normal_map: wgpu::TextureView,
     textures: [Texture; 2],
+    backdrop: Texture,
     sampler: wgpu::Sampler,
+    compute: Option<Compute>,
+    profiler: Arc<Profiler>,
+    atlas: Atlas,
+    batches: Vec<Vec<Id>>,
+    frames: Frames,
 }
 
 impl Pipeline {
-    fn new(device: &wgpu::Device, size: Size<u32>, format: wgpu::TextureFormat) -> Self {
-        let offset_alignment = device.limits().min_uniform_buffer_offset_alignment;
-
+    fn new(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        size: Size<u32>,
+        format: wgpu::TextureFormat,
+        intermediate: wgpu::TextureFormat,
+        backend: Backend,
+        profiler: Arc<Profiler>,
+    ) -> Self {
         let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
             label: Some("iced_blur sampler"),
             address_mode_u: wgpu::AddressMode::ClampToEdge,
@@ -86,18 +954,11 @@ impl Pipeline {
             ..Default::default()
         });
 
-        #[repr(C, align(256))]
-        #[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
-        struct Level {
-            size: u32,
-            _pad: [u32; 63],
-        }
-
         let texel_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
             label: Some("iced_blur texel bind group layout"),
             entries: &[wgpu::BindGroupLayoutEntry {
                 binding: 0,
-                visibility: wgpu::ShaderStages::FRAGMENT,
+                visibility: wgpu::ShaderStages::FRAGMENT | wgpu::ShaderStages::COMPUTE,
                 ty: wgpu::BindingType::Buffer {
                     ty: wgpu::BufferBindingType::Uniform,
                     has_dynamic_offset: true,
@@ -109,7 +970,7 @@ impl Pipeline {
             }],
         });
 
-        let levels = (0..=Shader::MAX_BLUR_RADIUS)
+        let levels = (0..=Primitive::MAX_BLUR_RADIUS)
             .map(|i| Level {
                 size: 2u32.pow(i),
                 _pad: bytemuck::Zeroable::zeroed(),
@@ -133,21 +994,65 @@ impl Pipeline {
             }],
         });
 
-        let size = Size::new(
-            size.width.next_power_of_two(),
-            size.height.next_power_of_two(),
-        );
-        let texture1 = Texture::new(device, size, format, &sampler);
+        let instance_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("iced_blur instance bind group layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT | wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: wgpu::BufferSize::new(
+                            std::mem::size_of::<Uniforms>() as wgpu::BufferAddress
+                        ),
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        });
+
+        // a normal map facing the viewer, which displaces nothing
+        let flat_normal_map =
+            normal_map_texture(device, queue, Size::new(1, 1), &[128, 128, 255, 255])
+                .create_view(&wgpu::TextureViewDescriptor::default());
+
+        let size = bucket(size, device.limits().max_texture_dimension_2d);
+        let texture1 = Texture::new(device, size, intermediate, &sampler);
 
         let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
             label: Some("iced_blur downsample render pipeline layout"),
-            bind_group_layouts: &[&texture1.bind_group_layout, &texel_layout],
+            bind_group_layouts: &[&texture1.bind_group_layout, &texel_layout, &instance_layout],
             push_constant_ranges: &[],
         });
 
         let downscale_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
             label: Some("iced_blur downsample shader"),
-            source: wgpu::ShaderSource::Wgsl(include_str!("shaders/downsample.wgsl").into()),
+            source: wgpu::ShaderSource::Wgsl(
+                concat!(
+                    include_str!("shaders/instance.wgsl"),
+                    include_str!("shaders/color.wgsl"),
+                    include_str!("shaders/edge.wgsl"),
+                    include_str!("shaders/downsample.wgsl")
+                )
+                .into(),
+            ),
         });
 
         let downscale_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
@@ -166,7 +1071,7 @@ impl Pipeline {
                 module: &downscale_shader,
                 entry_point: Some("fs_main"),
                 targets: &[Some(wgpu::ColorTargetState {
-                    format,
+                    format: intermediate,
                     blend: None,
                     write_mask: wgpu::ColorWrites::ALL,
                 })],
@@ -176,17 +1081,25 @@ impl Pipeline {
             cache: None,
         });
 
-        let texture2 = Texture::new(device, size, format, &sampler);
+        let texture2 = Texture::new(device, size, intermediate, &sampler);
 
         let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
             label: Some("iced_blur upsample render pipeline layout"),
-            bind_group_layouts: &[&texture2.bind_group_layout, &texel_layout],
+            bind_group_layouts: &[&texture2.bind_group_layout, &texel_layout, &instance_layout],
             push_constant_ranges: &[],
         });
 
         let upscale_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
             label: Some("iced_blur upsample shader"),
-            source: wgpu::ShaderSource::Wgsl(include_str!("shaders/upsample.wgsl").into()),
+            source: wgpu::ShaderSource::Wgsl(
+                concat!(
+                    include_str!("shaders/instance.wgsl"),
+                    include_str!("shaders/color.wgsl"),
+                    include_str!("shaders/edge.wgsl"),
+                    include_str!("shaders/upsample.wgsl")
+                )
+                .into(),
+            ),
         });
 
         let upscale_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
@@ -204,6 +1117,60 @@ impl Pipeline {
             fragment: Some(wgpu::FragmentState {
                 module: &upscale_shader,
                 entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: intermediate,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            }),
+            multiview: None,
+            cache: None,
+        });
+
+        let backdrop = Texture::new(device, size, format, &sampler);
+
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("iced_blur composite render pipeline layout"),
+            bind_group_layouts: &[
+                &texture2.bind_group_layout,
+                &texel_layout,
+                &instance_layout,
+                &backdrop.bind_group_layout,
+            ],
+            push_constant_ranges: &[],
+        });
+
+        let composite_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("iced_blur composite shader"),
+            source: wgpu::ShaderSource::Wgsl(
+                concat!(
+                    include_str!("shaders/instance.wgsl"),
+                    include_str!("shaders/color.wgsl"),
+                    include_str!("shaders/blend.wgsl"),
+                    include_str!("shaders/sdf.wgsl"),
+                    include_str!("shaders/edge.wgsl"),
+                    include_str!("shaders/composite.wgsl")
+                )
+                .into(),
+            ),
+        });
+
+        let composite_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("iced_blur composite render pipeline"),
+            layout: Some(&layout),
+            vertex: wgpu::VertexState {
+                module: &composite_shader,
+                entry_point: Some("vs_main"),
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+                buffers: &[],
+            },
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            fragment: Some(wgpu::FragmentState {
+                module: &composite_shader,
+                entry_point: Some("fs_main"),
                 targets: &[Some(wgpu::ColorTargetState {
                     format,
                     blend: None,
@@ -215,71 +1182,571 @@ impl Pipeline {
             cache: None,
         });
 
-        Self {
-            offset_alignment,
-            upscale_pipeline,
-            downscale_pipeline,
-            texel_bind_group,
-            sampler,
-            textures: [texture1, texture2],
+        let luminance_texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("iced_blur luminance texture"),
+            size: wgpu::Extent3d {
+                width: 1,
+                height: 1,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: Readback::FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+
+        let luminance_view = luminance_texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("iced_blur luminance render pipeline layout"),
+            bind_group_layouts: &[&texture1.bind_group_layout, &instance_layout],
+            push_constant_ranges: &[],
+        });
+
+        let luminance_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("iced_blur luminance shader"),
+            source: wgpu::ShaderSource::Wgsl(
+                concat!(
+                    include_str!("shaders/instance.wgsl"),
+                    include_str!("shaders/color.wgsl"),
+                    include_str!("shaders/luminance.wgsl")
+                )
+                .into(),
+            ),
+        });
+
+        let luminance_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("iced_blur luminance render pipeline"),
+            layout: Some(&layout),
+            vertex: wgpu::VertexState {
+                module: &luminance_shader,
+                entry_point: Some("vs_main"),
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+                buffers: &[],
+            },
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            fragment: Some(wgpu::FragmentState {
+                module: &luminance_shader,
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: Readback::FORMAT,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            }),
+            multiview: None,
+            cache: None,
+        });
+
+        let textures = [texture1, texture2];
+
+        let compute = (backend == Backend::Compute && Compute::is_supported(device)).then(|| {
+            Compute::new(
+                device,
+                &textures[0].bind_group_layout,
+                &texel_layout,
+                &instance_layout,
+                &textures,
+            )
+        });
+
+        Self {
+            upscale_pipeline,
+            composite_pipeline,
+            luminance_pipeline,
+            luminance_texture,
+            luminance_view,
+            downscale_pipeline,
+            texel_bind_group,
+            instance_layout,
+            instances: HashMap::new(),
+            flat_normal_map,
+            sampler,
+            textures,
+            backdrop,
+            compute,
+            profiler,
+            atlas: Atlas::default(),
+            batches: Vec::new(),
+            frames: Frames::default(),
+        }
+    }
+
+    // starts the next frame on the first region prepared after a frame was
+    // rendered, or prepared a second time, evicting the regions left out of
+    // the last ones before any is reused
+    fn begin(&mut self, device: &wgpu::Device, pool: &mut Pool, id: Id) {
+        if self.frames.begin(self.atlas.contains(id)) {
+            let extent = self.atlas.extent;
+
+            self.atlas = self.atlas.next();
+            self.batches.clear();
+
+            // shrinks the textures back to what the last frame needed, so they
+            // follow the window down after it was enlarged
+            if extent.width > 0 && extent.height > 0 {
+                self.resize(device, pool, extent);
+            }
+
+            let stale: Vec<Id> = self
+                .instances
+                .iter()
+                .filter(|(_, instance)| self.frames.is_stale(instance.last_used))
+                .map(|(id, _)| *id)
+                .collect();
+
+            for id in stale {
+                if let Some(frozen) = self
+                    .instances
+                    .remove(&id)
+                    .and_then(|instance| instance.frozen)
+                {
+                    frozen.release(pool);
+                }
+            }
+
+            pool.trim();
+        }
+    }
+
+    // places a capture of `size` in the atlas of the frame, growing the
+    // intermediate textures to fit it
+    fn allocate(
+        &mut self,
+        device: &wgpu::Device,
+        pool: &mut Pool,
+        id: Id,
+        size: Size<u32>,
+        batch: bool,
+    ) -> [u32; 2] {
+        let limit = device.limits().max_texture_dimension_2d;
+        let (position, wrapped) = self.atlas.allocate(id, size, batch, limit);
+
+        // the regions placed before a wrap may be overwritten by the ones
+        // after it, so they can no longer be blurred together
+        if wrapped && self.batches.last().is_some_and(|last| !last.is_empty()) {
+            self.batches.push(Vec::new());
+        }
+
+        // only grows within a frame, as the regions already placed keep
+        // their part of the textures
+        let current = self.backdrop.size();
+        let extent = self.atlas.extent;
+
+        self.resize(
+            device,
+            pool,
+            Size::new(
+                extent.width.max(current.width),
+                extent.height.max(current.height),
+            ),
+        );
+
+        position
+    }
+
+    // swaps the intermediate textures for ones fitting an atlas of `size`,
+    // returning the current ones to the pool
+    fn resize(&mut self, device: &wgpu::Device, pool: &mut Pool, size: Size<u32>) {
+        let size = bucket(size, device.limits().max_texture_dimension_2d);
+
+        if size == self.backdrop.size() {
+            return;
+        }
+
+        for texture in self.textures.iter_mut().chain([&mut self.backdrop]) {
+            let format = texture.texture.format();
+            let resized = pool.acquire(device, size, format, &self.sampler);
+
+            pool.release(std::mem::replace(texture, resized));
+        }
+
+        if let Some(compute) = &mut self.compute {
+            compute.update(device, &self.textures);
+        }
+
+        pool.trim();
+    }
+
+    // the offset of the uniforms of level `i` in the texel buffer, which
+    // are laid out at the stride of `Level` rather than the alignment the
+    // device requires, as that may be smaller
+    fn level(&self, i: u32) -> wgpu::DynamicOffset {
+        i * std::mem::size_of::<Level>() as wgpu::DynamicOffset
+    }
+
+    fn prepare(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, id: Id, region: Region<'_>) {
+        let Region {
+            capture,
+            uniforms,
+            normal_map,
+            luminance,
+            passes,
+            batch,
+        } = region;
+
+        let instance = self.instances.entry(id).or_insert_with(|| {
+            Instance::new(
+                device,
+                &self.instance_layout,
+                &self.flat_normal_map,
+                &self.sampler,
+            )
+        });
+
+        if instance.normal_map != normal_map.map(NormalMap::id) {
+            let view = normal_map.map(|normal_map| {
+                normal_map_texture(device, queue, normal_map.size(), normal_map.pixels())
+                    .create_view(&wgpu::TextureViewDescriptor::default())
+            });
+
+            instance.bind_group = instance_bind_group(
+                device,
+                &self.instance_layout,
+                &instance.buffer,
+                view.as_ref().unwrap_or(&self.flat_normal_map),
+                &self.sampler,
+            );
+            instance.normal_map = normal_map.map(NormalMap::id);
+        }
+
+        instance.batch = batch.then(|| {
+            if self.batches.is_empty() {
+                self.batches.push(Vec::new());
+            }
+
+            self.batches.last_mut().unwrap().push(id);
+            self.batches.len() - 1
+        });
+        instance.capture = capture;
+        instance.passes = passes;
+        instance.sample_luminance = luminance.is_some();
+        instance.blurred.store(false, atomic::Ordering::Relaxed);
+        instance.last_used = self.frames.current;
+        queue.write_buffer(&instance.buffer, 0, bytemuck::bytes_of(&uniforms));
+
+        if let Some(luminance) = luminance
+            && instance.readback.is_none()
+        {
+            instance.readback = Some(Readback::new(device, luminance));
+        }
+
+        if let Some(readback) = &instance.readback {
+            readback.poll(device);
+        }
+    }
+
+    // whether the region `id` can be composited from the level it kept,
+    // instead of being blurred again
+    fn reuses(&self, id: Id, capture: &Capture, radius: f32, power_policy: PowerPolicy) -> bool {
+        let Some(frozen) = self
+            .instances
+            .get(&id)
+            .and_then(|instance| instance.frozen.as_ref())
+        else {
+            return false;
+        };
+
+        // the level no longer matches a region that moved, resized or changed
+        // its radius
+        if !frozen.filled.load(atomic::Ordering::Relaxed)
+            || !frozen.capture.same_source(capture)
+            || frozen.radius != radius
+        {
+            return false;
+        }
+
+        match power_policy {
+            PowerPolicy::Live => false,
+            PowerPolicy::Throttled { fps } => {
+                frozen.blurred_at.elapsed() < Duration::from_secs(1) / fps.max(1)
+            }
+            PowerPolicy::Frozen => true,
+        }
+    }
+
+    // keeps the blurred level of the region `id` after it is blurred, unless it is
+    // blurred every frame, returning them to the pool then
+    fn freeze(
+        &mut self,
+        device: &wgpu::Device,
+        pool: &mut Pool,
+        id: Id,
+        radius: f32,
+        power_policy: PowerPolicy,
+        reused: bool,
+    ) {
+        let Some(instance) = self.instances.get_mut(&id) else {
+            return;
+        };
+
+        // a region that lost its level is blurred like any other
+        instance.reused = reused && instance.frozen.is_some();
+
+        if instance.reused {
+            instance.blurred.store(true, atomic::Ordering::Relaxed);
+            return;
+        }
+
+        if power_policy == PowerPolicy::Live {
+            if let Some(frozen) = instance.frozen.take() {
+                frozen.release(pool);
+            }
+
+            return;
+        }
+
+        // the level is kept at the size of the capture, outside of the atlas
+        if let Some(frozen) = instance
+            .frozen
+            .take_if(|frozen| frozen.blurred.size() != instance.capture.size)
+        {
+            frozen.release(pool);
+        }
+
+        let (blurred, backdrop) = match instance.frozen.take() {
+            Some(frozen) => (frozen.blurred, frozen.backdrop),
+            None => (
+                pool.acquire(
+                    device,
+                    instance.capture.size,
+                    self.textures[1].texture.format(),
+                    &self.sampler,
+                ),
+                pool.acquire(
+                    device,
+                    instance.capture.size,
+                    self.backdrop.texture.format(),
+                    &self.sampler,
+                ),
+            ),
+        };
+
+        // filled once the region is blurred in this frame
+        instance.frozen = Some(Frozen {
+            blurred,
+            backdrop,
+            capture: instance.capture,
+            radius,
+            blurred_at: Instant::now(),
+            filled: AtomicBool::new(false),
+        });
+    }
+
+    fn render(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        frame: &wgpu::Texture,
+        target: &wgpu::TextureView,
+        clip_bounds: &Rectangle<u32>,
+        id: Id,
+    ) {
+        self.frames.render();
+
+        let Some(instance) = self.instances.get(&id) else {
+            return;
+        };
+
+        if instance.capture.is_empty() {
+            return;
+        }
+
+        // the first region of a batch to be rendered blurs all of them
+        if !instance.blurred.load(atomic::Ordering::Relaxed) {
+            let batch = match instance.batch {
+                Some(batch) => self.batches[batch]
+                    .iter()
+                    .filter_map(|id| self.instances.get(id))
+                    .filter(|instance| !instance.capture.is_empty())
+                    .collect(),
+                None => vec![instance],
+            };
+
+            for instance in &batch {
+                instance.blurred.store(true, atomic::Ordering::Relaxed);
+            }
+
+            self.blur(encoder, frame, &batch);
+        }
+
+        // a reused region keeps its blurred level, but composites over the
+        // current backdrop, and measures the luminance of the level it kept
+        if instance.reused
+            && let Some(frozen) = &instance.frozen
+        {
+            let span = self.profiler.begin(encoder);
+            copy_backdrop(encoder, frame, &instance.capture, &frozen.backdrop);
+            self.profiler.end(encoder, span, Stage::Copy, 0);
+
+            let span = self.profiler.begin(encoder);
+            let passes = self.measure_luminance(encoder, instance, &frozen.blurred) as u32;
+            self.profiler.end(encoder, span, Stage::Downsample, passes);
+        }
+
+        self.composite(encoder, target, clip_bounds, instance);
+    }
+
+    // blurs the backdrop of every region in `batch` up to level 1, with a
+    // single pass per level for all of them
+    fn blur(&self, encoder: &mut wgpu::CommandEncoder, frame: &wgpu::Texture, batch: &[&Instance]) {
+        let radius = batch
+            .iter()
+            .map(|instance| instance.passes)
+            .max()
+            .unwrap_or(0);
+
+        // copy each region and its margin from the framebuffer into its part
+        // of `backdrop`
+        let span = self.profiler.begin(encoder);
+
+        for instance in batch {
+            copy_backdrop(encoder, frame, &instance.capture, &self.backdrop);
+        }
+
+        self.profiler.end(encoder, span, Stage::Copy, 0);
+
+        // downsample
+        let span = self.profiler.begin(encoder);
+        let mut passes = radius;
+
+        if let Some(compute) = &self.compute {
+            compute.downsample(encoder, self, batch, radius);
+            passes = 1;
+        } else {
+            for i in 1..=radius {
+                let (dst, src) = if i == 1 {
+                    (&self.textures[1].view, &self.backdrop.bind_group)
+                } else if i.is_multiple_of(2) {
+                    (&self.textures[0].view, &self.textures[1].bind_group)
+                } else {
+                    (&self.textures[1].view, &self.textures[0].bind_group)
+                };
+
+                let mut render_pass = texture_render_pass(encoder, dst);
+
+                render_pass.set_pipeline(&self.downscale_pipeline);
+                render_pass.set_bind_group(0, src, &[]);
+                render_pass.set_bind_group(1, &self.texel_bind_group, &[self.level(i - 1)]);
+
+                for instance in batch.iter().filter(|instance| instance.passes >= i) {
+                    instance.capture.set_viewport(&mut render_pass);
+                    render_pass.set_bind_group(2, &instance.bind_group, &[]);
+                    render_pass.draw(0..6, 0..1);
+                }
+            }
         }
-    }
 
-    fn update(&mut self, device: &wgpu::Device, size: Size<u32>) {
-        for texture in &mut self.textures {
-            texture.update(device, size, &self.sampler);
+        // average the most blurred level of each region into
+        // `luminance_texture`
+        for instance in batch {
+            let blurred = &self.textures[if instance.passes.is_multiple_of(2) {
+                0
+            } else {
+                1
+            }];
+
+            if self.measure_luminance(encoder, instance, blurred) {
+                passes += 1;
+            }
         }
-    }
 
-    fn render(
-        &self,
-        encoder: &mut wgpu::CommandEncoder,
-        frame: &wgpu::Texture,
-        target: &wgpu::TextureView,
-        clip_bounds: &Rectangle<u32>,
-        radius: u32,
-    ) {
-        let radius = radius.clamp(1, Shader::MAX_BLUR_RADIUS);
+        // the luminance is measured along with the downsample passes
+        self.profiler.end(encoder, span, Stage::Downsample, passes);
 
-        // copy framebuffer into `textures[0]`
-        {
-            let source = wgpu::TexelCopyTextureInfoBase {
-                origin: wgpu::Origin3d {
-                    x: clip_bounds.x,
-                    y: clip_bounds.y,
-                    z: 0,
-                },
-                ..frame.as_image_copy()
-            };
+        // upsample, leaving the last level to `composite`
+        let span = self.profiler.begin(encoder);
+        let mut passes = radius.saturating_sub(1);
 
-            let copy_size = wgpu::Extent3d {
-                width: clip_bounds.width,
-                height: clip_bounds.height,
-                depth_or_array_layers: 1,
+        if let Some(compute) = &self.compute {
+            compute.upsample(encoder, self, batch, radius);
+            passes = passes.min(1);
+        } else {
+            for i in (1..radius).rev() {
+                let (dst, src) = if i.is_multiple_of(2) {
+                    (&self.textures[0].view, &self.textures[1].bind_group)
+                } else {
+                    (&self.textures[1].view, &self.textures[0].bind_group)
+                };
+
+                let mut render_pass = texture_render_pass(encoder, dst);
+
+                render_pass.set_pipeline(&self.upscale_pipeline);
+                render_pass.set_bind_group(0, src, &[]);
+                render_pass.set_bind_group(1, &self.texel_bind_group, &[self.level(i)]);
+
+                for instance in batch.iter().filter(|instance| instance.passes > i) {
+                    instance.capture.set_viewport(&mut render_pass);
+                    render_pass.set_bind_group(2, &instance.bind_group, &[]);
+                    render_pass.draw(0..6, 0..1);
+                }
+            }
+        }
+
+        self.profiler.end(encoder, span, Stage::Upsample, passes);
+
+        // keep the level the frozen regions are composited from, until they
+        // are blurred again
+        let span = self.profiler.begin(encoder);
+
+        for instance in batch {
+            let Some(frozen) = &instance.frozen else {
+                continue;
             };
 
+            let capture = instance.capture;
+
             encoder.copy_texture_to_texture(
-                source,
-                self.textures[0].texture.as_image_copy(),
-                copy_size,
+                wgpu::TexelCopyTextureInfoBase {
+                    origin: wgpu::Origin3d {
+                        x: capture.atlas[0],
+                        y: capture.atlas[1],
+                        z: 0,
+                    },
+                    ..self.textures[1].texture.as_image_copy()
+                },
+                frozen.blurred.texture.as_image_copy(),
+                wgpu::Extent3d {
+                    width: capture.size.width,
+                    height: capture.size.height,
+                    depth_or_array_layers: 1,
+                },
             );
+
+            frozen.filled.store(true, atomic::Ordering::Relaxed);
         }
 
-        // downsample
-        for i in 1..=radius {
-            let (dst, src) = if i.is_multiple_of(2) {
-                (&self.textures[0].view, &self.textures[1].bind_group)
-            } else {
-                (&self.textures[1].view, &self.textures[0].bind_group)
-            };
+        self.profiler.end(encoder, span, Stage::Copy, 0);
+    }
+
+    // averages the luminance of a region in `blurred` into
+    // `luminance_texture` and reads it back, returning whether it was measured
+    fn measure_luminance(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        instance: &Instance,
+        blurred: &Texture,
+    ) -> bool {
+        let Some(readback) = &instance.readback else {
+            return false;
+        };
+
+        if !instance.sample_luminance || !readback.is_idle() {
+            return false;
+        }
 
+        {
             let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: Some("iced_blur texture render pass"),
+                label: Some("iced_blur luminance render pass"),
                 color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: dst,
+                    view: &self.luminance_view,
                     resolve_target: None,
                     ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Load,
+                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
                         store: wgpu::StoreOp::Store,
                     },
                     depth_slice: None,
@@ -289,58 +1756,359 @@ impl Pipeline {
                 occlusion_query_set: None,
             });
 
-            let offset =
-                (i - 1 as wgpu::DynamicOffset) * (self.offset_alignment as wgpu::DynamicOffset);
-            render_pass.set_pipeline(&self.downscale_pipeline);
-            render_pass.set_bind_group(0, src, &[]);
-            render_pass.set_bind_group(1, &self.texel_bind_group, &[offset]);
+            render_pass.set_pipeline(&self.luminance_pipeline);
+            render_pass.set_bind_group(0, &blurred.bind_group, &[]);
+            render_pass.set_bind_group(1, &instance.bind_group, &[]);
             render_pass.draw(0..6, 0..1);
         }
 
-        // upsample
-        for i in (0..radius).rev() {
-            let (dst, src) = if i == 0 {
-                (target, &self.textures[1].bind_group)
-            } else if i.is_multiple_of(2) {
-                (&self.textures[0].view, &self.textures[1].bind_group)
-            } else {
-                (&self.textures[1].view, &self.textures[0].bind_group)
-            };
+        readback.copy(encoder, &self.luminance_texture);
 
-            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: Some("iced_blur texture render pass"),
-                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: dst,
-                    resolve_target: None,
-                    ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Load,
-                        store: wgpu::StoreOp::Store,
-                    },
-                    depth_slice: None,
-                })],
-                depth_stencil_attachment: None,
-                timestamp_writes: None,
-                occlusion_query_set: None,
-            });
+        true
+    }
 
-            let offset =
-                (i as wgpu::DynamicOffset) * (self.offset_alignment as wgpu::DynamicOffset);
-
-            if i == 0 {
-                render_pass.set_viewport(
-                    clip_bounds.x as f32,
-                    clip_bounds.y as f32,
-                    clip_bounds.width as f32,
-                    clip_bounds.height as f32,
-                    0.0,
-                    1.0,
-                );
-            }
-            render_pass.set_pipeline(&self.upscale_pipeline);
-            render_pass.set_bind_group(0, src, &[]);
-            render_pass.set_bind_group(1, &self.texel_bind_group, &[offset]);
+    // upsamples the last level of a region straight into the frame
+    fn composite(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        target: &wgpu::TextureView,
+        clip_bounds: &Rectangle<u32>,
+        instance: &Instance,
+    ) {
+        // reused regions are composited from the level they kept
+        let (blurred, backdrop) = match &instance.frozen {
+            Some(frozen) if instance.reused => (&frozen.blurred, &frozen.backdrop),
+            _ => (&self.textures[1], &self.backdrop),
+        };
+
+        let span = self.profiler.begin(encoder);
+
+        {
+            let mut render_pass = texture_render_pass(encoder, target);
+
+            render_pass.set_viewport(
+                clip_bounds.x as f32,
+                clip_bounds.y as f32,
+                clip_bounds.width as f32,
+                clip_bounds.height as f32,
+                0.0,
+                1.0,
+            );
+            render_pass.set_pipeline(&self.composite_pipeline);
+            render_pass.set_bind_group(0, &blurred.bind_group, &[]);
+            render_pass.set_bind_group(1, &self.texel_bind_group, &[self.level(0)]);
+            render_pass.set_bind_group(2, &instance.bind_group, &[]);
+            render_pass.set_bind_group(3, &backdrop.bind_group, &[]);
             render_pass.draw(0..6, 0..1);
         }
+
+        self.profiler.end(encoder, span, Stage::Composite, 1);
+    }
+}
+
+// copies the visible part of a region and its margin from the framebuffer
+// into its part of `destination`
+fn copy_backdrop(
+    encoder: &mut wgpu::CommandEncoder,
+    frame: &wgpu::Texture,
+    capture: &Capture,
+    destination: &Texture,
+) {
+    encoder.copy_texture_to_texture(
+        wgpu::TexelCopyTextureInfoBase {
+            origin: wgpu::Origin3d {
+                x: capture.source.x,
+                y: capture.source.y,
+                z: 0,
+            },
+            ..frame.as_image_copy()
+        },
+        wgpu::TexelCopyTextureInfoBase {
+            origin: wgpu::Origin3d {
+                x: capture.atlas[0] + capture.offset[0],
+                y: capture.atlas[1] + capture.offset[1],
+                z: 0,
+            },
+            ..destination.texture.as_image_copy()
+        },
+        wgpu::Extent3d {
+            width: capture.source.width,
+            height: capture.source.height,
+            depth_or_array_layers: 1,
+        },
+    );
+}
+
+fn texture_render_pass<'a>(
+    encoder: &'a mut wgpu::CommandEncoder,
+    view: &wgpu::TextureView,
+) -> wgpu::RenderPass<'a> {
+    encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+        label: Some("iced_blur texture render pass"),
+        color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+            view,
+            resolve_target: None,
+            ops: wgpu::Operations {
+                load: wgpu::LoadOp::Load,
+                store: wgpu::StoreOp::Store,
+            },
+            depth_slice: None,
+        })],
+        depth_stencil_attachment: None,
+        timestamp_writes: None,
+        occlusion_query_set: None,
+    })
+}
+
+struct Instance {
+    buffer: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+    normal_map: Option<Id>,
+    capture: Capture,
+    passes: u32,
+    batch: Option<usize>,
+    blurred: AtomicBool,
+    reused: bool,
+    frozen: Option<Frozen>,
+    sample_luminance: bool,
+    readback: Option<Readback>,
+    last_used: u64,
+}
+
+// counts the frames a pipeline rendered, so that the regions left out of a
+// number of them are evicted, however long a static screen goes without
+// rendering
+#[derive(Debug, Default)]
+struct Frames {
+    current: u64,
+    rendered: AtomicBool,
+}
+
+impl Frames {
+    const TIMEOUT: u64 = 60;
+
+    // starts the next frame once the last one was rendered, or when
+    // `restart`, returning whether it did
+    fn begin(&mut self, restart: bool) -> bool {
+        let next = self.rendered.swap(false, atomic::Ordering::Relaxed) || restart;
+
+        if next {
+            self.current += 1;
+        }
+
+        next
+    }
+
+    fn render(&self) {
+        self.rendered.store(true, atomic::Ordering::Relaxed);
+    }
+
+    fn is_stale(&self, last_used: u64) -> bool {
+        self.current - last_used > Self::TIMEOUT
+    }
+}
+
+// the blurred level a region was last composited from, kept while its power
+// policy holds off blurring it again, and the texture its current backdrop is
+// copied into on every frame in between
+struct Frozen {
+    blurred: Texture,
+    backdrop: Texture,
+    capture: Capture,
+    radius: f32,
+    blurred_at: Instant,
+    filled: AtomicBool,
+}
+
+impl Frozen {
+    fn release(self, pool: &mut Pool) {
+        pool.release(self.blurred);
+        pool.release(self.backdrop);
+    }
+}
+
+impl Instance {
+    fn new(
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        normal_map: &wgpu::TextureView,
+        sampler: &wgpu::Sampler,
+    ) -> Self {
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("iced_blur instance buffer"),
+            size: std::mem::size_of::<Uniforms>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let bind_group = instance_bind_group(device, layout, &buffer, normal_map, sampler);
+
+        Self {
+            buffer,
+            bind_group,
+            normal_map: None,
+            capture: Capture::default(),
+            passes: 0,
+            batch: None,
+            blurred: AtomicBool::new(false),
+            reused: false,
+            frozen: None,
+            sample_luminance: false,
+            readback: None,
+            last_used: 0,
+        }
+    }
+}
+
+fn instance_bind_group(
+    device: &wgpu::Device,
+    layout: &wgpu::BindGroupLayout,
+    buffer: &wgpu::Buffer,
+    normal_map: &wgpu::TextureView,
+    sampler: &wgpu::Sampler,
+) -> wgpu::BindGroup {
+    device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some("iced_blur instance bind group"),
+        layout,
+        entries: &[
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: buffer.as_entire_binding(),
+            },
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: wgpu::BindingResource::TextureView(normal_map),
+            },
+            wgpu::BindGroupEntry {
+                binding: 2,
+                resource: wgpu::BindingResource::Sampler(sampler),
+            },
+        ],
+    })
+}
+
+// normals are data, so they are never decoded as sRGB
+fn normal_map_texture(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    size: Size<u32>,
+    pixels: &[u8],
+) -> wgpu::Texture {
+    device.create_texture_with_data(
+        queue,
+        &wgpu::TextureDescriptor {
+            label: Some("iced_blur normal map"),
+            size: wgpu::Extent3d {
+                width: size.width,
+                height: size.height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8Unorm,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        },
+        wgpu::util::TextureDataOrder::LayerMajor,
+        pixels,
+    )
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ReadbackState {
+    Idle,
+    Copied,
+    Mapping,
+    Mapped,
+}
+
+// reads the luminance of a region back to the CPU, mapping the buffer on the
+// frame after it was copied into, once its commands have been submitted
+struct Readback {
+    buffer: wgpu::Buffer,
+    state: Arc<Mutex<ReadbackState>>,
+    luminance: Arc<Luminance>,
+}
+
+impl Readback {
+    const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::R32Float;
+
+    fn new(device: &wgpu::Device, luminance: Arc<Luminance>) -> Self {
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("iced_blur luminance readback buffer"),
+            size: wgpu::COPY_BYTES_PER_ROW_ALIGNMENT as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        Self {
+            buffer,
+            state: Arc::new(Mutex::new(ReadbackState::Idle)),
+            luminance,
+        }
+    }
+
+    fn is_idle(&self) -> bool {
+        *self.state.lock().unwrap() == ReadbackState::Idle
+    }
+
+    fn copy(&self, encoder: &mut wgpu::CommandEncoder, texture: &wgpu::Texture) {
+        encoder.copy_texture_to_buffer(
+            texture.as_image_copy(),
+            wgpu::TexelCopyBufferInfo {
+                buffer: &self.buffer,
+                layout: wgpu::TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: Some(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT),
+                    rows_per_image: None,
+                },
+            },
+            wgpu::Extent3d {
+                width: 1,
+                height: 1,
+                depth_or_array_layers: 1,
+            },
+        );
+
+        *self.state.lock().unwrap() = ReadbackState::Copied;
+    }
+
+    fn poll(&self, device: &wgpu::Device) {
+        let state = *self.state.lock().unwrap();
+
+        if state == ReadbackState::Copied {
+            *self.state.lock().unwrap() = ReadbackState::Mapping;
+
+            let shared = self.state.clone();
+
+            self.buffer
+                .slice(..)
+                .map_async(wgpu::MapMode::Read, move |result| {
+                    *shared.lock().unwrap() = if result.is_ok() {
+                        ReadbackState::Mapped
+                    } else {
+                        ReadbackState::Idle
+                    };
+                });
+        }
+
+        if state != ReadbackState::Idle {
+            let _ = device.poll(wgpu::PollType::Poll);
+        }
+
+        if *self.state.lock().unwrap() == ReadbackState::Mapped {
+            let value = {
+                let bytes = self.buffer.slice(..).get_mapped_range();
+
+                bytemuck::pod_read_unaligned::<f32>(&bytes[..4])
+            };
+
+            self.buffer.unmap();
+            *self.state.lock().unwrap() = ReadbackState::Idle;
+
+            self.luminance.set(value);
+        }
     }
 }
 
@@ -364,6 +2132,22 @@ impl Texture {
             depth_or_array_layers: 1,
         };
 
+        // frozen regions copy their blurred level out of the intermediate
+        // textures
+        let mut usage = wgpu::TextureUsages::RENDER_ATTACHMENT
+            | wgpu::TextureUsages::TEXTURE_BINDING
+            | wgpu::TextureUsages::COPY_SRC
+            | wgpu::TextureUsages::COPY_DST;
+
+        // lets the compute backend write into the intermediate textures
+        if format
+            .guaranteed_format_features(device.features())
+            .allowed_usages
+            .contains(wgpu::TextureUsages::STORAGE_BINDING)
+        {
+            usage |= wgpu::TextureUsages::STORAGE_BINDING;
+        }
+
         let texture = device.create_texture(&wgpu::TextureDescriptor {
             label: Some("iced_blur texture"),
             size,
@@ -371,9 +2155,7 @@ impl Texture {
             sample_count: 1,
             dimension: wgpu::TextureDimension::D2,
             format,
-            usage: wgpu::TextureUsages::RENDER_ATTACHMENT
-                | wgpu::TextureUsages::TEXTURE_BINDING
-                | wgpu::TextureUsages::COPY_DST,
+            usage,
             view_formats: &[],
         });
 
@@ -387,7 +2169,7 @@ impl Texture {
             entries: &[
                 wgpu::BindGroupLayoutEntry {
                     binding: 0,
-                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    visibility: wgpu::ShaderStages::FRAGMENT | wgpu::ShaderStages::COMPUTE,
                     ty: wgpu::BindingType::Texture {
                         sample_type: wgpu::TextureSampleType::Float { filterable: true },
                         view_dimension: wgpu::TextureViewDimension::D2,
@@ -397,7 +2179,7 @@ impl Texture {
                 },
                 wgpu::BindGroupLayoutEntry {
                     binding: 1,
-                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    visibility: wgpu::ShaderStages::FRAGMENT | wgpu::ShaderStages::COMPUTE,
                     ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
                     count: None,
                 },
@@ -419,17 +2201,247 @@ impl Texture {
             ],
         });
 
-        Self {
+        let texture = Self {
             texture,
             view,
             bind_group,
             bind_group_layout,
+        };
+
+        memory::allocate(texture.bytes());
+
+        texture
+    }
+
+    fn size(&self) -> Size<u32> {
+        Size::new(self.texture.width(), self.texture.height())
+    }
+
+    fn bytes(&self) -> u64 {
+        let texel = self.texture.format().block_copy_size(None).unwrap_or(4);
+
+        u64::from(self.texture.width()) * u64::from(self.texture.height()) * u64::from(texel)
+    }
+}
+
+impl Drop for Texture {
+    fn drop(&mut self) {
+        memory::free(self.bytes());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FRAME: Size<u32> = Size::new(1920, 1080);
+
+    fn rectangle(x: u32, y: u32, width: u32, height: u32) -> Rectangle<u32> {
+        Rectangle {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    #[test]
+    fn capture_inside_the_frame() {
+        let capture = Capture::new([100, 200], Size::new(300, 400), FRAME, [10, 20]);
+
+        assert_eq!(capture.source, rectangle(100, 200, 300, 400));
+        assert_eq!(capture.offset, [0, 0]);
+        assert_eq!(capture.valid(), [0.0, 0.0, 300.0, 400.0]);
+        assert_eq!(capture.texture_origin(), [90.0, 180.0]);
+        assert!(!capture.is_empty());
+    }
+
+    #[test]
+    fn capture_partly_off_screen() {
+        let capture = Capture::new([-10, -20], Size::new(100, 100), FRAME, [0, 0]);
+
+        assert_eq!(capture.source, rectangle(0, 0, 90, 80));
+        assert_eq!(capture.offset, [10, 20]);
+        assert_eq!(capture.valid(), [10.0, 20.0, 100.0, 100.0]);
+
+        let capture = Capture::new([1900, 1000], Size::new(100, 100), FRAME, [0, 0]);
+
+        assert_eq!(capture.source, rectangle(1900, 1000, 20, 80));
+        assert_eq!(capture.offset, [0, 0]);
+        assert_eq!(capture.valid(), [0.0, 0.0, 20.0, 80.0]);
+    }
+
+    #[test]
+    fn capture_off_screen() {
+        assert!(Capture::new([-200, 0], Size::new(100, 100), FRAME, [0, 0]).is_empty());
+        assert!(Capture::new([0, 1080], Size::new(100, 100), FRAME, [0, 0]).is_empty());
+    }
+
+    #[test]
+    fn capture_same_source_ignores_atlas() {
+        let capture = Capture::new([10, 10], Size::new(100, 100), FRAME, [0, 0]);
+
+        assert!(capture.same_source(&Capture::new(
+            [10, 10],
+            Size::new(100, 100),
+            FRAME,
+            [50, 50]
+        )));
+        assert!(!capture.same_source(&Capture::new([11, 10], Size::new(100, 100), FRAME, [0, 0])));
+    }
+
+    #[test]
+    fn capture_margin_stays_within_the_frame_when_clamped() {
+        let primitive = Primitive::new(Id::unique(), 16.0, 1.0, BlendMode::Normal);
+
+        assert_eq!(
+            primitive.capture_span(0.0, 1920.0, 1.0, 1920, 2048),
+            (0, 1920)
+        );
+        assert_eq!(
+            primitive.capture_span(100.0, 200.0, 1.0, 1920, 2048),
+            (0, 556)
+        );
+        assert_eq!(
+            primitive.capture_span(0.0, 1500.0, 2.0, 3000, 2048),
+            (0, 2048)
+        );
+    }
+
+    #[test]
+    fn capture_margin_is_trimmed_to_the_limit() {
+        let primitive =
+            Primitive::new(Id::unique(), 16.0, 1.0, BlendMode::Normal).edge_mode(EdgeMode::Mirror);
+
+        assert_eq!(
+            primitive.capture_span(100.0, 200.0, 1.0, 1920, 2048),
+            (-156, 712)
+        );
+        assert_eq!(
+            primitive.capture_span(0.0, 1920.0, 1.0, 1920, 2048),
+            (-64, 2048)
+        );
+    }
+
+    #[test]
+    fn frames_only_advance_once_rendered() {
+        let mut frames = Frames::default();
+
+        // a static screen prepares its frozen regions again without
+        // rendering, which keeps them however long it takes
+        for _ in 0..Frames::TIMEOUT * 2 {
+            assert!(!frames.begin(false));
         }
+
+        assert_eq!(frames.current, 0);
+        assert!(!frames.is_stale(0));
+
+        frames.render();
+
+        assert!(frames.begin(false));
+        assert!(!frames.begin(false));
+        assert!(frames.begin(true));
+        assert_eq!(frames.current, 2);
     }
 
-    fn update(&mut self, device: &wgpu::Device, size: Size<u32>, sampler: &wgpu::Sampler) {
-        if self.texture.width() != size.width || self.texture.height() != size.height {
-            *self = Texture::new(device, size, self.texture.format(), &sampler);
+    #[test]
+    fn frames_evict_regions_left_out() {
+        let mut frames = Frames::default();
+
+        for _ in 0..Frames::TIMEOUT {
+            frames.render();
+            frames.begin(false);
         }
+
+        assert!(!frames.is_stale(0));
+
+        frames.render();
+        frames.begin(false);
+
+        assert!(frames.is_stale(0));
+        assert!(!frames.is_stale(frames.current));
+    }
+
+    #[test]
+    fn bucket_rounds_up_within_the_limit() {
+        assert_eq!(bucket(Size::new(0, 256), 2048), Size::new(256, 256));
+        assert_eq!(bucket(Size::new(1919, 1081), 2048), Size::new(2048, 1280));
+        assert_eq!(bucket(Size::new(1921, 4000), 2048), Size::new(2048, 2048));
+    }
+
+    #[test]
+    fn atlas_packs_batched_regions_in_rows() {
+        let mut atlas = Atlas::default();
+
+        assert_eq!(
+            atlas.allocate(Id(0), Size::new(60, 10), true, 100),
+            ([0, 0], false)
+        );
+        assert_eq!(
+            atlas.allocate(Id(1), Size::new(30, 20), true, 100),
+            ([60, 0], false)
+        );
+        assert_eq!(
+            atlas.allocate(Id(2), Size::new(60, 20), true, 100),
+            ([0, 20], false)
+        );
+        assert_eq!(atlas.extent, Size::new(90, 40));
+        assert!(atlas.contains(Id(1)));
+        assert!(!atlas.contains(Id(3)));
+    }
+
+    #[test]
+    fn atlas_wraps_at_the_limit() {
+        let mut atlas = Atlas::default();
+
+        assert_eq!(
+            atlas.allocate(Id(0), Size::new(100, 60), true, 100),
+            ([0, 0], false)
+        );
+        assert_eq!(
+            atlas.allocate(Id(1), Size::new(100, 60), true, 100),
+            ([0, 0], true)
+        );
+        assert_eq!(atlas.extent, Size::new(100, 60));
+    }
+
+    #[test]
+    fn atlas_shares_a_slot_between_unbatched_regions() {
+        let mut atlas = Atlas::default();
+
+        assert_eq!(
+            atlas.allocate(Id(0), Size::new(50, 50), false, 1000),
+            ([0, 0], false)
+        );
+        assert_eq!(
+            atlas.allocate(Id(1), Size::new(40, 40), false, 1000),
+            ([0, 0], false)
+        );
+        assert_eq!(
+            atlas.allocate(Id(2), Size::new(20, 20), true, 1000),
+            ([50, 0], false)
+        );
+        assert_eq!(atlas.extent, Size::new(70, 50));
+
+        // a larger region moves the slot, growing it to fit every region
+        assert_eq!(
+            atlas.allocate(Id(3), Size::new(60, 30), false, 1000),
+            ([70, 0], false)
+        );
+        assert_eq!(
+            atlas.allocate(Id(4), Size::new(55, 45), false, 1000),
+            ([70, 0], false)
+        );
+        assert_eq!(atlas.extent, Size::new(130, 50));
+
+        // the next frame sizes the slot to the largest region up front
+        let mut next = atlas.next();
+
+        assert_eq!(
+            next.allocate(Id(0), Size::new(10, 10), false, 1000),
+            ([0, 0], false)
+        );
+        assert_eq!(next.extent, Size::new(60, 50));
+        assert!(!next.contains(Id(1)));
     }
 }
@@ -0,0 +1,206 @@
+use super::{Instance, Pipeline, Texture};
+
+use iced_core::Size;
+use iced_widget::renderer::wgpu::wgpu;
+
+// runs the downsample and upsample passes as compute dispatches that write
+// into storage textures, so a whole chain fits in a single compute pass
+pub struct Compute {
+    downsample: wgpu::ComputePipeline,
+    upsample: wgpu::ComputePipeline,
+    target_layout: wgpu::BindGroupLayout,
+    targets: [wgpu::BindGroup; 2],
+}
+
+impl Compute {
+    // the intermediate format the shaders write to
+    pub const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+    const WORKGROUP_SIZE: u32 = 8;
+
+    pub fn is_supported(device: &wgpu::Device) -> bool {
+        device.limits().max_compute_invocations_per_workgroup
+            >= Self::WORKGROUP_SIZE * Self::WORKGROUP_SIZE
+            && Self::FORMAT
+                .guaranteed_format_features(device.features())
+                .allowed_usages
+                .contains(wgpu::TextureUsages::STORAGE_BINDING)
+    }
+
+    pub fn new(
+        device: &wgpu::Device,
+        texture_layout: &wgpu::BindGroupLayout,
+        texel_layout: &wgpu::BindGroupLayout,
+        instance_layout: &wgpu::BindGroupLayout,
+        textures: &[Texture; 2],
+    ) -> Self {
+        let target_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("iced_blur storage texture layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::COMPUTE,
+                ty: wgpu::BindingType::StorageTexture {
+                    access: wgpu::StorageTextureAccess::WriteOnly,
+                    format: Self::FORMAT,
+                    view_dimension: wgpu::TextureViewDimension::D2,
+                },
+                count: None,
+            }],
+        });
+
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("iced_blur compute pipeline layout"),
+            bind_group_layouts: &[
+                texture_layout,
+                texel_layout,
+                instance_layout,
+                &target_layout,
+            ],
+            push_constant_ranges: &[],
+        });
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("iced_blur compute shader"),
+            source: wgpu::ShaderSource::Wgsl(
+                concat!(
+                    include_str!("../shaders/instance.wgsl"),
+                    include_str!("../shaders/color.wgsl"),
+                    include_str!("../shaders/edge.wgsl"),
+                    include_str!("../shaders/compute.wgsl")
+                )
+                .into(),
+            ),
+        });
+
+        let downsample = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("iced_blur downsample compute pipeline"),
+            layout: Some(&layout),
+            module: &shader,
+            entry_point: Some("downsample"),
+            compilation_options: wgpu::PipelineCompilationOptions::default(),
+            cache: None,
+        });
+
+        let upsample = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("iced_blur upsample compute pipeline"),
+            layout: Some(&layout),
+            module: &shader,
+            entry_point: Some("upsample"),
+            compilation_options: wgpu::PipelineCompilationOptions::default(),
+            cache: None,
+        });
+
+        let targets = targets(device, &target_layout, textures);
+
+        Self {
+            downsample,
+            upsample,
+            target_layout,
+            targets,
+        }
+    }
+
+    // rebinds the intermediate textures after they were recreated
+    pub fn update(&mut self, device: &wgpu::Device, textures: &[Texture; 2]) {
+        self.targets = targets(device, &self.target_layout, textures);
+    }
+
+    // blurs the backdrops of a batch down to their last level, ending in the
+    // same textures as the render passes would
+    pub fn downsample(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        pipeline: &Pipeline,
+        batch: &[&Instance],
+        radius: u32,
+    ) {
+        let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("iced_blur downsample compute pass"),
+            timestamp_writes: None,
+        });
+
+        compute_pass.set_pipeline(&self.downsample);
+
+        for i in 1..=radius {
+            let (target, src) = if i == 1 {
+                (&self.targets[1], &pipeline.backdrop.bind_group)
+            } else if i.is_multiple_of(2) {
+                (&self.targets[0], &pipeline.textures[1].bind_group)
+            } else {
+                (&self.targets[1], &pipeline.textures[0].bind_group)
+            };
+
+            compute_pass.set_bind_group(0, src, &[]);
+            compute_pass.set_bind_group(1, &pipeline.texel_bind_group, &[pipeline.level(i - 1)]);
+            compute_pass.set_bind_group(3, target, &[]);
+
+            for instance in batch.iter().filter(|instance| instance.passes >= i) {
+                compute_pass.set_bind_group(2, &instance.bind_group, &[]);
+                self.dispatch(&mut compute_pass, instance.capture.size);
+            }
+        }
+    }
+
+    // blurs the batch back up to level 1, leaving the last pass, which
+    // composites into the frame, to the render pipeline
+    pub fn upsample(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        pipeline: &Pipeline,
+        batch: &[&Instance],
+        radius: u32,
+    ) {
+        if radius <= 1 {
+            return;
+        }
+
+        let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("iced_blur upsample compute pass"),
+            timestamp_writes: None,
+        });
+
+        compute_pass.set_pipeline(&self.upsample);
+
+        for i in (1..radius).rev() {
+            let (target, src) = if i.is_multiple_of(2) {
+                (&self.targets[0], &pipeline.textures[1].bind_group)
+            } else {
+                (&self.targets[1], &pipeline.textures[0].bind_group)
+            };
+
+            compute_pass.set_bind_group(0, src, &[]);
+            compute_pass.set_bind_group(1, &pipeline.texel_bind_group, &[pipeline.level(i)]);
+            compute_pass.set_bind_group(3, target, &[]);
+
+            for instance in batch.iter().filter(|instance| instance.passes > i) {
+                compute_pass.set_bind_group(2, &instance.bind_group, &[]);
+                self.dispatch(&mut compute_pass, instance.capture.size);
+            }
+        }
+    }
+
+    // one invocation per pixel of a capture
+    fn dispatch(&self, compute_pass: &mut wgpu::ComputePass<'_>, size: Size<u32>) {
+        compute_pass.dispatch_workgroups(
+            size.width.div_ceil(Self::WORKGROUP_SIZE),
+            size.height.div_ceil(Self::WORKGROUP_SIZE),
+            1,
+        );
+    }
+}
+
+fn targets(
+    device: &wgpu::Device,
+    layout: &wgpu::BindGroupLayout,
+    textures: &[Texture; 2],
+) -> [wgpu::BindGroup; 2] {
+    textures.each_ref().map(|texture| {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("iced_blur storage texture bind group"),
+            layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::TextureView(&texture.view),
+            }],
+        })
+    })
+}
@@ -0,0 +1,296 @@
+pub use profiler::{Profiler, Span};
+
+// the stages of a blur that are measured separately
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stage {
+    Copy,
+    Downsample,
+    Upsample,
+    Composite,
+}
+
+#[cfg(feature = "profiling")]
+mod profiler {
+    use super::Stage;
+    use crate::memory;
+    use crate::stats::{self, BlurStats};
+
+    use iced_core::time::Duration;
+    use iced_widget::renderer::wgpu::wgpu;
+
+    use std::sync::atomic::{self, AtomicBool};
+    use std::sync::{Arc, Mutex};
+
+    // the timestamp written when a stage started, if the frame is timed
+    #[derive(Debug, Clone, Copy)]
+    pub struct Span(Option<u32>);
+
+    // measures the stages of every blur in a frame with timestamp queries,
+    // and publishes their totals once they are read back
+    pub struct Profiler {
+        timestamps: Option<Timestamps>,
+        frame: Mutex<Frame>,
+        rendered: AtomicBool,
+    }
+
+    #[derive(Default)]
+    struct Frame {
+        spans: Vec<Stage>,
+        passes: u32,
+        timed: bool,
+    }
+
+    impl Profiler {
+        pub fn new(device: &wgpu::Device, queue: &wgpu::Queue) -> Self {
+            let features =
+                wgpu::Features::TIMESTAMP_QUERY | wgpu::Features::TIMESTAMP_QUERY_INSIDE_ENCODERS;
+
+            Self {
+                timestamps: device
+                    .features()
+                    .contains(features)
+                    .then(|| Timestamps::new(device, queue)),
+                frame: Mutex::new(Frame {
+                    timed: true,
+                    ..Frame::default()
+                }),
+                rendered: AtomicBool::new(false),
+            }
+        }
+
+        // publishes the stats of the last frame once the next one starts
+        pub fn prepare(&self, device: &wgpu::Device) {
+            if !self.rendered.swap(false, atomic::Ordering::Relaxed) {
+                return;
+            }
+
+            let mut frame = self.frame.lock().unwrap();
+            let last = std::mem::take(&mut *frame);
+
+            let mut stats = BlurStats {
+                texture_bytes: memory::memory_usage().in_use,
+                passes: last.passes,
+                ..stats::blur_stats()
+            };
+
+            if let Some(timestamps) = &self.timestamps {
+                if last.timed && !last.spans.is_empty() {
+                    timestamps.map(last.spans);
+                }
+
+                if let Some([copy, downsample, upsample, composite]) = timestamps.poll(device) {
+                    stats.copy = Some(copy);
+                    stats.downsample = Some(downsample);
+                    stats.upsample = Some(upsample);
+                    stats.composite = Some(composite);
+                }
+
+                // the readback buffer can only be written while it is not
+                // mapped
+                frame.timed = timestamps.is_idle();
+            }
+
+            stats::publish(stats);
+        }
+
+        pub fn begin(&self, encoder: &mut wgpu::CommandEncoder) -> Span {
+            self.rendered.store(true, atomic::Ordering::Relaxed);
+
+            let frame = self.frame.lock().unwrap();
+
+            let Some(timestamps) = &self.timestamps else {
+                return Span(None);
+            };
+
+            let index = frame.spans.len() as u32;
+
+            if !frame.timed || index >= Timestamps::CAPACITY {
+                return Span(None);
+            }
+
+            encoder.write_timestamp(&timestamps.query_set, 2 * index);
+
+            Span(Some(index))
+        }
+
+        pub fn end(
+            &self,
+            encoder: &mut wgpu::CommandEncoder,
+            span: Span,
+            stage: Stage,
+            passes: u32,
+        ) {
+            let mut frame = self.frame.lock().unwrap();
+
+            frame.passes += passes;
+
+            if let (Some(index), Some(timestamps)) = (span.0, &self.timestamps) {
+                timestamps.record(encoder, index);
+                frame.spans.push(stage);
+            }
+        }
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum State {
+        Idle,
+        Mapping,
+        Mapped,
+    }
+
+    // the queries of a frame, resolved into a buffer that is mapped on a
+    // later frame, after its commands were submitted
+    struct Timestamps {
+        query_set: wgpu::QuerySet,
+        resolve: wgpu::Buffer,
+        readback: wgpu::Buffer,
+        period: f32,
+        state: Arc<Mutex<State>>,
+        pending: Mutex<Vec<Stage>>,
+    }
+
+    impl Timestamps {
+        const CAPACITY: u32 = 128;
+        const SPAN_SIZE: wgpu::BufferAddress = 2 * std::mem::size_of::<u64>() as u64;
+
+        fn new(device: &wgpu::Device, queue: &wgpu::Queue) -> Self {
+            let query_set = device.create_query_set(&wgpu::QuerySetDescriptor {
+                label: Some("iced_blur timestamp query set"),
+                ty: wgpu::QueryType::Timestamp,
+                count: 2 * Self::CAPACITY,
+            });
+
+            // each span is resolved on its own, at an aligned offset
+            let resolve = device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("iced_blur timestamp resolve buffer"),
+                size: u64::from(Self::CAPACITY) * wgpu::QUERY_RESOLVE_BUFFER_ALIGNMENT,
+                usage: wgpu::BufferUsages::QUERY_RESOLVE | wgpu::BufferUsages::COPY_SRC,
+                mapped_at_creation: false,
+            });
+
+            let readback = device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("iced_blur timestamp readback buffer"),
+                size: u64::from(Self::CAPACITY) * Self::SPAN_SIZE,
+                usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+                mapped_at_creation: false,
+            });
+
+            Self {
+                query_set,
+                resolve,
+                readback,
+                period: queue.get_timestamp_period(),
+                state: Arc::new(Mutex::new(State::Idle)),
+                pending: Mutex::new(Vec::new()),
+            }
+        }
+
+        fn is_idle(&self) -> bool {
+            *self.state.lock().unwrap() == State::Idle
+        }
+
+        // writes the end of the span at `index` and copies it into the
+        // readback buffer
+        fn record(&self, encoder: &mut wgpu::CommandEncoder, index: u32) {
+            let offset = u64::from(index) * wgpu::QUERY_RESOLVE_BUFFER_ALIGNMENT;
+
+            encoder.write_timestamp(&self.query_set, 2 * index + 1);
+            encoder.resolve_query_set(
+                &self.query_set,
+                2 * index..2 * index + 2,
+                &self.resolve,
+                offset,
+            );
+            encoder.copy_buffer_to_buffer(
+                &self.resolve,
+                offset,
+                &self.readback,
+                u64::from(index) * Self::SPAN_SIZE,
+                Self::SPAN_SIZE,
+            );
+        }
+
+        fn map(&self, spans: Vec<Stage>) {
+            *self.pending.lock().unwrap() = spans;
+            *self.state.lock().unwrap() = State::Mapping;
+
+            let shared = self.state.clone();
+
+            self.readback
+                .slice(..)
+                .map_async(wgpu::MapMode::Read, move |result| {
+                    *shared.lock().unwrap() = if result.is_ok() {
+                        State::Mapped
+                    } else {
+                        State::Idle
+                    };
+                });
+        }
+
+        // the total time of each stage, once the readback buffer is mapped
+        fn poll(&self, device: &wgpu::Device) -> Option<[Duration; 4]> {
+            if self.is_idle() {
+                return None;
+            }
+
+            let _ = device.poll(wgpu::PollType::Poll);
+
+            if *self.state.lock().unwrap() != State::Mapped {
+                return None;
+            }
+
+            let mut totals = [Duration::ZERO; 4];
+
+            {
+                let bytes = self.readback.slice(..).get_mapped_range();
+                let timestamps: &[u64] = bytemuck::cast_slice(&bytes);
+
+                for (i, stage) in self.pending.lock().unwrap().drain(..).enumerate() {
+                    let ticks = timestamps[2 * i + 1].saturating_sub(timestamps[2 * i]);
+
+                    totals[stage as usize] +=
+                        Duration::from_nanos((ticks as f64 * f64::from(self.period)) as u64);
+                }
+            }
+
+            self.readback.unmap();
+            *self.state.lock().unwrap() = State::Idle;
+
+            Some(totals)
+        }
+    }
+}
+
+#[cfg(not(feature = "profiling"))]
+mod profiler {
+    use super::Stage;
+
+    use iced_widget::renderer::wgpu::wgpu;
+
+    // records nothing without the `profiling` feature
+    pub struct Profiler;
+
+    #[derive(Debug, Clone, Copy)]
+    pub struct Span;
+
+    impl Profiler {
+        pub fn new(_device: &wgpu::Device, _queue: &wgpu::Queue) -> Self {
+            Self
+        }
+
+        pub fn prepare(&self, _device: &wgpu::Device) {}
+
+        pub fn begin(&self, _encoder: &mut wgpu::CommandEncoder) -> Span {
+            Span
+        }
+
+        pub fn end(
+            &self,
+            _encoder: &mut wgpu::CommandEncoder,
+            _span: Span,
+            _stage: Stage,
+            _passes: u32,
+        ) {
+        }
+    }
+}
@@ -0,0 +1,544 @@
+use super::{Id, Pool, Texture, can_copy, fail, pack, probe, scope};
+use crate::BlendMode;
+use crate::error::Error;
+use crate::mode::{self, Mode};
+
+use iced_core::time::{Duration, Instant};
+use iced_core::{Color, Rectangle, Size};
+use iced_widget::renderer::wgpu::wgpu;
+use iced_widget::shader;
+
+use std::collections::HashMap;
+
+// copies the backdrop under the vibrant content, before its text and images
+// are drawn
+#[derive(Debug)]
+pub struct Capture {
+    id: Id,
+    color: Color,
+    blend_mode: BlendMode,
+}
+
+impl Capture {
+    pub fn new(id: Id, color: Color, blend_mode: BlendMode) -> Self {
+        Self {
+            id,
+            color,
+            blend_mode,
+        }
+    }
+}
+
+impl shader::Primitive for Capture {
+    fn prepare(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        format: wgpu::TextureFormat,
+        storage: &mut shader::Storage,
+        bounds: &Rectangle,
+        viewport: &shader::Viewport,
+    ) {
+        let scale_factor = viewport.scale_factor() as f32;
+        let region = Rectangle {
+            x: (bounds.x * scale_factor).round() as u32,
+            y: (bounds.y * scale_factor).round() as u32,
+            width: (bounds.width * scale_factor).ceil() as u32,
+            height: (bounds.height * scale_factor).ceil() as u32,
+        };
+
+        let supported = probe(device, format);
+
+        mode::degrade(supported);
+
+        if supported == Mode::Fallback {
+            return;
+        }
+
+        if storage.has::<Error>() {
+            return;
+        }
+
+        let prepared = scope(device, || {
+            if !storage.has::<Pipeline>() {
+                storage.store(Pipeline::new(device, format));
+            }
+
+            let Some(pipeline) = storage.get_mut::<Pipeline>() else {
+                return;
+            };
+
+            pipeline.prepare(
+                device,
+                queue,
+                self.id,
+                region,
+                Uniforms {
+                    color: pack(self.color, format),
+                    origin: [region.x as f32, region.y as f32],
+                    blend_mode: self.blend_mode as u32,
+                    _pad0: 0,
+                },
+            );
+        });
+
+        if let Err(error) = prepared {
+            fail(storage, error);
+        }
+    }
+
+    fn render(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        storage: &shader::Storage,
+        target: &wgpu::TextureView,
+        clip_bounds: &Rectangle<u32>,
+    ) {
+        if !can_copy(target) {
+            return;
+        }
+
+        if storage.has::<Error>() {
+            return;
+        }
+
+        let Some(pipeline) = storage.get::<Pipeline>() else {
+            return;
+        };
+
+        if let Some(instance) = pipeline.instances.get(&self.id) {
+            instance.copy(encoder, target.texture(), &instance.backdrop, clip_bounds);
+        }
+    }
+}
+
+// keeps the vibrant content drawn in white, and puts the backdrop back for it
+// to be drawn again in black
+#[derive(Debug)]
+pub struct Swap {
+    id: Id,
+}
+
+impl Swap {
+    pub fn new(id: Id) -> Self {
+        Self { id }
+    }
+}
+
+impl shader::Primitive for Swap {
+    fn prepare(
+        &self,
+        _device: &wgpu::Device,
+        _queue: &wgpu::Queue,
+        _format: wgpu::TextureFormat,
+        _storage: &mut shader::Storage,
+        _bounds: &Rectangle,
+        _viewport: &shader::Viewport,
+    ) {
+    }
+
+    fn render(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        storage: &shader::Storage,
+        target: &wgpu::TextureView,
+        clip_bounds: &Rectangle<u32>,
+    ) {
+        if !can_copy(target) {
+            return;
+        }
+
+        if storage.has::<Error>() {
+            return;
+        }
+
+        if let Some(pipeline) = storage.get::<Pipeline>() {
+            pipeline.swap(encoder, target, clip_bounds, self.id);
+        }
+    }
+}
+
+// recolors the vibrant content drawn over the captured backdrop
+#[derive(Debug)]
+pub struct Composite {
+    id: Id,
+}
+
+impl Composite {
+    pub fn new(id: Id) -> Self {
+        Self { id }
+    }
+}
+
+impl shader::Primitive for Composite {
+    fn prepare(
+        &self,
+        _device: &wgpu::Device,
+        _queue: &wgpu::Queue,
+        _format: wgpu::TextureFormat,
+        _storage: &mut shader::Storage,
+        _bounds: &Rectangle,
+        _viewport: &shader::Viewport,
+    ) {
+    }
+
+    fn render(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        storage: &shader::Storage,
+        target: &wgpu::TextureView,
+        clip_bounds: &Rectangle<u32>,
+    ) {
+        if !can_copy(target) {
+            return;
+        }
+
+        if storage.has::<Error>() {
+            return;
+        }
+
+        if let Some(pipeline) = storage.get::<Pipeline>() {
+            pipeline.render(encoder, target, clip_bounds, self.id);
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct Uniforms {
+    color: [f32; 4],
+    origin: [f32; 2],
+    blend_mode: u32,
+    _pad0: u32,
+}
+
+pub struct Pipeline {
+    pipeline: wgpu::RenderPipeline,
+    restore_pipeline: wgpu::RenderPipeline,
+    uniform_layout: wgpu::BindGroupLayout,
+    instances: HashMap<Id, Instance>,
+    pool: Pool,
+    sampler: wgpu::Sampler,
+    format: wgpu::TextureFormat,
+}
+
+impl Pipeline {
+    const INSTANCE_TIMEOUT: Duration = Duration::from_secs(1);
+
+    fn new(device: &wgpu::Device, format: wgpu::TextureFormat) -> Self {
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("iced_blur vibrant sampler"),
+            ..Default::default()
+        });
+
+        let uniform_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("iced_blur vibrant uniform bind group layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: wgpu::BufferSize::new(
+                        std::mem::size_of::<Uniforms>() as wgpu::BufferAddress
+                    ),
+                },
+                count: None,
+            }],
+        });
+
+        // only used for its bind group layout
+        let texture = Texture::new(device, Size::new(1, 1), format, &sampler);
+
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("iced_blur vibrant render pipeline layout"),
+            bind_group_layouts: &[
+                &texture.bind_group_layout,
+                &texture.bind_group_layout,
+                &texture.bind_group_layout,
+                &uniform_layout,
+            ],
+            push_constant_ranges: &[],
+        });
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("iced_blur vibrant shader"),
+            source: wgpu::ShaderSource::Wgsl(
+                concat!(
+                    include_str!("../shaders/blend.wgsl"),
+                    include_str!("../shaders/vibrant.wgsl")
+                )
+                .into(),
+            ),
+        });
+
+        let render_pipeline = |label, entry_point| {
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some(label),
+                layout: Some(&layout),
+                vertex: wgpu::VertexState {
+                    module: &shader,
+                    entry_point: Some("vs_main"),
+                    compilation_options: wgpu::PipelineCompilationOptions::default(),
+                    buffers: &[],
+                },
+                primitive: wgpu::PrimitiveState::default(),
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState::default(),
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader,
+                    entry_point: Some(entry_point),
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format,
+                        blend: None,
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                    compilation_options: wgpu::PipelineCompilationOptions::default(),
+                }),
+                multiview: None,
+                cache: None,
+            })
+        };
+
+        let pipeline = render_pipeline("iced_blur vibrant render pipeline", "fs_main");
+        let restore_pipeline =
+            render_pipeline("iced_blur vibrant restore render pipeline", "fs_restore");
+
+        Self {
+            pipeline,
+            restore_pipeline,
+            uniform_layout,
+            instances: HashMap::new(),
+            pool: Pool::default(),
+            sampler,
+            format,
+        }
+    }
+
+    fn prepare(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        id: Id,
+        region: Rectangle<u32>,
+        uniforms: Uniforms,
+    ) {
+        let now = Instant::now();
+
+        let expired: Vec<Id> = self
+            .instances
+            .iter()
+            .filter(|(_, instance)| {
+                now.saturating_duration_since(instance.last_used) >= Self::INSTANCE_TIMEOUT
+            })
+            .map(|(id, _)| *id)
+            .collect();
+
+        for id in expired {
+            if let Some(instance) = self.instances.remove(&id) {
+                instance.release(&mut self.pool);
+            }
+        }
+
+        let size = Size::new(region.width.max(1), region.height.max(1));
+
+        let instance = self.instances.entry(id).or_insert_with(|| {
+            Instance::new(
+                device,
+                &mut self.pool,
+                size,
+                self.format,
+                &self.sampler,
+                &self.uniform_layout,
+            )
+        });
+
+        // swaps the textures for ones of the new size, through the pool
+        if instance.backdrop.size() != size {
+            for texture in [
+                &mut instance.backdrop,
+                &mut instance.white,
+                &mut instance.black,
+            ] {
+                let resized = self.pool.acquire(device, size, self.format, &self.sampler);
+
+                self.pool.release(std::mem::replace(texture, resized));
+            }
+        }
+
+        self.pool.trim();
+
+        instance.region = region;
+        instance.last_used = now;
+
+        queue.write_buffer(&instance.buffer, 0, bytemuck::bytes_of(&uniforms));
+    }
+
+    fn swap(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        target: &wgpu::TextureView,
+        clip_bounds: &Rectangle<u32>,
+        id: Id,
+    ) {
+        let Some(instance) = self.instances.get(&id) else {
+            return;
+        };
+
+        instance.copy(encoder, target.texture(), &instance.white, clip_bounds);
+        instance.draw(encoder, target, clip_bounds, &self.restore_pipeline);
+    }
+
+    fn render(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        target: &wgpu::TextureView,
+        clip_bounds: &Rectangle<u32>,
+        id: Id,
+    ) {
+        let Some(instance) = self.instances.get(&id) else {
+            return;
+        };
+
+        instance.copy(encoder, target.texture(), &instance.black, clip_bounds);
+        instance.draw(encoder, target, clip_bounds, &self.pipeline);
+    }
+}
+
+struct Instance {
+    region: Rectangle<u32>,
+    backdrop: Texture,
+    white: Texture,
+    black: Texture,
+    buffer: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+    last_used: Instant,
+}
+
+impl Instance {
+    fn new(
+        device: &wgpu::Device,
+        pool: &mut Pool,
+        size: Size<u32>,
+        format: wgpu::TextureFormat,
+        sampler: &wgpu::Sampler,
+        layout: &wgpu::BindGroupLayout,
+    ) -> Self {
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("iced_blur vibrant uniform buffer"),
+            size: std::mem::size_of::<Uniforms>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("iced_blur vibrant uniform bind group"),
+            layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: buffer.as_entire_binding(),
+            }],
+        });
+
+        Self {
+            region: Rectangle::default(),
+            backdrop: pool.acquire(device, size, format, sampler),
+            white: pool.acquire(device, size, format, sampler),
+            black: pool.acquire(device, size, format, sampler),
+            buffer,
+            bind_group,
+            last_used: Instant::now(),
+        }
+    }
+
+    fn release(self, pool: &mut Pool) {
+        pool.release(self.backdrop);
+        pool.release(self.white);
+        pool.release(self.black);
+    }
+
+    // draws over the region with `pipeline`, which reads the textures of the
+    // instance
+    fn draw(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        target: &wgpu::TextureView,
+        clip_bounds: &Rectangle<u32>,
+        pipeline: &wgpu::RenderPipeline,
+    ) {
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("iced_blur vibrant render pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: target,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: wgpu::StoreOp::Store,
+                },
+                depth_slice: None,
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+
+        render_pass.set_viewport(
+            clip_bounds.x as f32,
+            clip_bounds.y as f32,
+            clip_bounds.width as f32,
+            clip_bounds.height as f32,
+            0.0,
+            1.0,
+        );
+        render_pass.set_pipeline(pipeline);
+        render_pass.set_bind_group(0, &self.backdrop.bind_group, &[]);
+        render_pass.set_bind_group(1, &self.white.bind_group, &[]);
+        render_pass.set_bind_group(2, &self.black.bind_group, &[]);
+        render_pass.set_bind_group(3, &self.bind_group, &[]);
+        render_pass.draw(0..6, 0..1);
+    }
+
+    // copies the visible part of the region from the framebuffer, keeping
+    // texels aligned with the region even when it is partially clipped
+    fn copy(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        frame: &wgpu::Texture,
+        texture: &Texture,
+        clip_bounds: &Rectangle<u32>,
+    ) {
+        let x = clip_bounds.x.max(self.region.x);
+        let y = clip_bounds.y.max(self.region.y);
+
+        let right = (clip_bounds.x + clip_bounds.width)
+            .min(self.region.x + texture.texture.width())
+            .min(frame.width());
+        let bottom = (clip_bounds.y + clip_bounds.height)
+            .min(self.region.y + texture.texture.height())
+            .min(frame.height());
+
+        if right <= x || bottom <= y {
+            return;
+        }
+
+        encoder.copy_texture_to_texture(
+            wgpu::TexelCopyTextureInfoBase {
+                origin: wgpu::Origin3d { x, y, z: 0 },
+                ..frame.as_image_copy()
+            },
+            wgpu::TexelCopyTextureInfoBase {
+                origin: wgpu::Origin3d {
+                    x: x - self.region.x,
+                    y: y - self.region.y,
+                    z: 0,
+                },
+                ..texture.texture.as_image_copy()
+            },
+            wgpu::Extent3d {
+                width: right - x,
+                height: bottom - y,
+                depth_or_array_layers: 1,
+            },
+        );
+    }
+}
@@ -0,0 +1,77 @@
+const BLEND_NORMAL: u32 = 0u;
+const BLEND_MULTIPLY: u32 = 1u;
+const BLEND_SCREEN: u32 = 2u;
+const BLEND_OVERLAY: u32 = 3u;
+const BLEND_SOFT_LIGHT: u32 = 4u;
+const BLEND_LUMINOSITY: u32 = 5u;
+
+fn luminance(color: vec3<f32>) -> f32 {
+    return dot(color, vec3<f32>(0.3, 0.59, 0.11));
+}
+
+fn set_luminance(color: vec3<f32>, lum: f32) -> vec3<f32> {
+    let c = color + (lum - luminance(color));
+    let l = luminance(c);
+    let n = min(min(c.r, c.g), c.b);
+    let x = max(max(c.r, c.g), c.b);
+
+    var result = c;
+
+    if n < 0.0 {
+        result = l + (result - l) * l / max(l - n, 1e-5);
+    }
+
+    if x > 1.0 {
+        result = l + (result - l) * (1.0 - l) / max(x - l, 1e-5);
+    }
+
+    return result;
+}
+
+fn overlay(backdrop: vec3<f32>, source: vec3<f32>) -> vec3<f32> {
+    let low = 2.0 * backdrop * source;
+    let high = 1.0 - 2.0 * (1.0 - backdrop) * (1.0 - source);
+
+    return select(high, low, backdrop <= vec3(0.5));
+}
+
+fn soft_light(backdrop: vec3<f32>, source: vec3<f32>) -> vec3<f32> {
+    let d = select(
+        sqrt(backdrop),
+        ((16.0 * backdrop - 12.0) * backdrop + 4.0) * backdrop,
+        backdrop <= vec3(0.25),
+    );
+
+    let low = backdrop - (1.0 - 2.0 * source) * backdrop * (1.0 - backdrop);
+    let high = backdrop + (2.0 * source - 1.0) * (d - backdrop);
+
+    return select(high, low, source <= vec3(0.5));
+}
+
+// blends `source` onto `backdrop` following the separable and non-separable
+// blend modes of the W3C compositing specification
+fn blend(mode: u32, backdrop: vec3<f32>, source: vec3<f32>) -> vec3<f32> {
+    var result = source;
+
+    switch mode {
+        case BLEND_MULTIPLY: {
+            result = backdrop * source;
+        }
+        case BLEND_SCREEN: {
+            result = backdrop + source - backdrop * source;
+        }
+        case BLEND_OVERLAY: {
+            result = overlay(backdrop, source);
+        }
+        case BLEND_SOFT_LIGHT: {
+            result = soft_light(backdrop, source);
+        }
+        case BLEND_LUMINOSITY: {
+            result = set_luminance(backdrop, luminance(source));
+        }
+        default: {}
+    }
+
+    return result;
+}
+
@@ -0,0 +1,40 @@
+// converts between sRGB encoded and linear colors, for surfaces that store
+// encoded colors without an sRGB view
+fn to_linear(color: vec3<f32>) -> vec3<f32> {
+    let c = max(color, vec3<f32>(0.0));
+
+    return select(pow((c + 0.055) / 1.055, vec3<f32>(2.4)), c / 12.92, c <= vec3<f32>(0.04045));
+}
+
+fn to_srgb(color: vec3<f32>) -> vec3<f32> {
+    let c = max(color, vec3<f32>(0.0));
+
+    return select(1.055 * pow(c, vec3<f32>(1.0 / 2.4)) - 0.055, c * 12.92, c <= vec3<f32>(0.0031308));
+}
+
+fn premultiply(color: vec4<f32>) -> vec4<f32> {
+    return vec4<f32>(color.rgb * color.a, color.a);
+}
+
+fn unpremultiply(color: vec4<f32>) -> vec4<f32> {
+    return select(vec4<f32>(color.rgb / color.a, color.a), vec4<f32>(0.0), color.a <= 0.0);
+}
+
+// puts a straight `layer` over a premultiplied `color`
+fn over(color: vec4<f32>, layer: vec4<f32>) -> vec4<f32> {
+    return premultiply(layer) + color * (1.0 - layer.a);
+}
+
+// colors are converted unpremultiplied, so partially transparent pixels keep
+// their hue
+fn decode(color: vec4<f32>, linearize: bool) -> vec4<f32> {
+    let straight = unpremultiply(color);
+
+    return select(color, premultiply(vec4<f32>(to_linear(straight.rgb), straight.a)), linearize);
+}
+
+fn encode(color: vec4<f32>, linearize: bool) -> vec4<f32> {
+    let straight = unpremultiply(color);
+
+    return select(color, premultiply(vec4<f32>(to_srgb(straight.rgb), straight.a)), linearize);
+}
@@ -0,0 +1,164 @@
+@group(0) @binding(0) var u_texture: texture_2d<f32>;
+@group(0) @binding(1) var u_sampler: sampler;
+
+struct Texel { size: u32, _pad0: vec4<u32>, _pad1: vec3<u32> };
+@group(1) @binding(0) var<uniform> u_texel: Texel;
+
+@group(2) @binding(0) var<uniform> u_instance: Instance;
+@group(2) @binding(1) var u_normal_map: texture_2d<f32>;
+@group(2) @binding(2) var u_normal_map_sampler: sampler;
+
+@group(3) @binding(0) var u_backdrop: texture_2d<f32>;
+@group(3) @binding(1) var u_backdrop_sampler: sampler;
+
+struct VertexInput {
+    @builtin(vertex_index) vertex_index: u32,
+}
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+}
+
+fn vertex_position(vertex_index: u32) -> vec2<f32> {
+    // #: 0 1 2 3 4 5
+    // x: 1 1 0 0 0 1
+    // y: 1 0 0 0 1 1
+    return vec2<f32>((vec2(1u, 2u) + vertex_index) % vec2(6u) < vec2(3u));
+}
+
+@vertex
+fn vs_main(input: VertexInput) -> VertexOutput {
+    var uv = vertex_position(input.vertex_index);
+
+    var out: VertexOutput;
+
+    out.uv = uv;
+    out.position = vec4<f32>(uv * vec2(2.0, -2.0) + vec2(-1.0, 1.0), 0.0, 1.0);
+
+    return out;
+}
+
+fn upsample(uv: vec2<f32>) -> vec4<f32> {
+    var texel_size = vec2<f32>(1) / vec2<f32>(textureDimensions(u_texture));
+    var texel = texel_size * vec2<f32>(u_texel.size) * u_instance.spread;
+
+    const sample = vec2<f32>(-1.0, 1.0);
+    const sample2 = vec2<f32>(0.0, 2.0);
+
+    return (1.0 / 6.0) * (
+                    sample_capture(uv+texel*sample.xx) +
+                    sample_capture(uv+texel*sample.yx) +
+                    sample_capture(uv+texel*sample.xy) +
+                    sample_capture(uv+texel*sample.yy)
+                ) + 
+                (1.0 / 12.0) * (
+                    sample_capture(uv+texel*sample2.xy)+
+                    sample_capture(uv-texel*sample2.xy)+
+                    sample_capture(uv+texel*sample2.yx)+
+                    sample_capture(uv-texel*sample2.yx)
+                );
+}
+
+const DISPLACEMENT_NORMAL_MAP: u32 = 1u;
+
+fn noise(position: vec2<f32>) -> f32 {
+    return fract(sin(dot(position, vec2<f32>(12.9898, 78.233))) * 43758.5453);
+}
+
+fn stop_offset(i: u32) -> f32 {
+    return u_instance.offsets[i / 4u][i % 4u];
+}
+
+// the color of the border gradient at `position`, or the border color
+fn border_color(position: vec2<f32>) -> vec4<f32> {
+    if u_instance.stop_count == 0u {
+        return u_instance.border_color;
+    }
+
+    let start = u_instance.gradient.xy;
+    let direction = u_instance.gradient.zw - start;
+    let t = clamp(dot(position - start, direction) / max(dot(direction, direction), 1e-5), 0.0, 1.0);
+
+    var color = u_instance.stops[0];
+
+    for (var i = 1u; i < u_instance.stop_count; i++) {
+        let previous = stop_offset(i - 1u);
+        let current = stop_offset(i);
+
+        if t > previous {
+            let progress = clamp((t - previous) / max(current - previous, 1e-5), 0.0, 1.0);
+            color = mix(u_instance.stops[i - 1u], u_instance.stops[i], progress);
+        }
+    }
+
+    return color;
+}
+
+// outward normal of the region edge closest to `position`
+fn region_normal(position: vec2<f32>) -> vec2<f32> {
+    let dx = vec2<f32>(0.5, 0.0);
+    let dy = vec2<f32>(0.0, 0.5);
+    let size = u_instance.size;
+    let radii = u_instance.border_radius;
+
+    let gradient = vec2<f32>(
+        region_distance(position + dx, size, radii) - region_distance(position - dx, size, radii),
+        region_distance(position + dy, size, radii) - region_distance(position - dy, size, radii),
+    );
+
+    return gradient / max(length(gradient), 1e-5);
+}
+
+@fragment
+fn fs_main(input: VertexOutput) -> @location(0) vec4<f32> {
+    let position = input.position.xy - u_instance.origin;
+    let distance = region_distance(position, u_instance.size, u_instance.border_radius);
+    let coverage = clamp(0.5 - distance, 0.0, 1.0);
+    let normal = region_normal(position);
+
+    // the textures start at the capture origin, margin included
+    let pixel = 1.0 / vec2<f32>(textureDimensions(u_texture));
+    let captured = (input.position.xy - u_instance.capture_origin) * pixel;
+
+    // bends the content inwards, either fading out away from the edge or
+    // following the normal map
+    let band = max(u_instance.refraction * 8.0, 1.0);
+    let edge = 1.0 - clamp(-distance / band, 0.0, 1.0);
+    let mapped = textureSample(u_normal_map, u_normal_map_sampler, position / u_instance.size).xy * 2.0 - 1.0;
+    let displacement = select(-normal * edge * edge, -mapped, u_instance.displacement == DISPLACEMENT_NORMAL_MAP);
+    let uv = captured + displacement * u_instance.refraction * pixel;
+
+    let blurred = upsample(uv);
+    let linearize = u_instance.linearize != 0u;
+    let backdrop = decode(textureSample(u_backdrop, u_backdrop_sampler, captured), linearize);
+    let refracted = decode(textureSample(u_backdrop, u_backdrop_sampler, uv), linearize);
+
+    let border_width = u_instance.border_width;
+    let stroke = select(0.0, clamp(0.5 + distance + border_width, 0.0, 1.0), border_width > 0.0);
+
+    let shadow_distance = region_distance(
+        position - u_instance.shadow_offset,
+        u_instance.size,
+        u_instance.border_radius,
+    );
+    let shadow = clamp(0.5 + shadow_distance / max(u_instance.shadow_blur, 1.0), 0.0, 1.0);
+
+    // lit from the top left
+    let facing = max(dot(normal, normalize(vec2<f32>(-1.0, -1.0))), 0.0);
+    let highlight_width = u_instance.highlight_width;
+    let highlight = select(0.0, clamp(0.5 + distance + highlight_width, 0.0, 1.0), highlight_width > 0.0) * facing;
+
+    // the framebuffer holds premultiplied colors, so the layers are put over
+    // the content and blend modes apply to the unpremultiplied colors
+    var color = select(blurred, refracted, u_instance.refract_backdrop != 0u);
+    color = over(color, u_instance.tint);
+    color = vec4(blend(u_instance.blend_mode, unpremultiply(backdrop).rgb, unpremultiply(color).rgb) * color.a, color.a);
+    color = over(color, vec4(u_instance.shadow_color.rgb, u_instance.shadow_color.a * shadow));
+    color = over(color, vec4(u_instance.highlight_color.rgb, u_instance.highlight_color.a * highlight));
+    color += vec4(vec3((noise(input.position.xy) - 0.5) * u_instance.noise * color.a), 0.0);
+    let border = border_color(input.position.xy);
+    color = over(color, vec4(border.rgb, border.a * stroke));
+
+    return encode(mix(backdrop, color, u_instance.opacity * coverage), linearize);
+}
@@ -0,0 +1,141 @@
+@group(0) @binding(0) var u_texture: texture_2d<f32>;
+@group(0) @binding(1) var u_sampler: sampler;
+
+struct Texel { size: u32, _pad0: vec4<u32>, _pad1: vec3<u32> };
+@group(1) @binding(0) var<uniform> u_texel: Texel;
+
+@group(2) @binding(0) var<uniform> u_instance: Instance;
+
+@group(3) @binding(0) var u_target: texture_storage_2d<rgba16float, write>;
+
+const WORKGROUP: u32 = 8u;
+const APRON: i32 = 8;
+const TILE: i32 = 24;
+
+// the texels under a workgroup and an apron around it, shared by the taps of
+// its invocations
+var<workgroup> tile: array<vec4<f32>, 576>;
+
+fn fetch(uv: vec2<f32>, edge: bool) -> vec4<f32> {
+    if edge {
+        return sample_edge(uv);
+    }
+
+    return sample_capture(uv);
+}
+
+fn load_tile(origin: vec2<i32>, index: u32, edge: bool) {
+    let dimensions = vec2<f32>(textureDimensions(u_texture));
+
+    for (var i = i32(index); i < TILE * TILE; i += i32(WORKGROUP * WORKGROUP)) {
+        let texel = origin - APRON + vec2<i32>(i % TILE, i / TILE);
+
+        tile[i] = fetch((vec2<f32>(texel) + 0.5) / dimensions, edge);
+    }
+
+    workgroupBarrier();
+}
+
+// filters the shared texels bilinearly at `position`, in pixels
+fn sample_tile(origin: vec2<i32>, position: vec2<f32>) -> vec4<f32> {
+    let p = position - 0.5 - vec2<f32>(origin - APRON);
+    let base = vec2<i32>(floor(p));
+    let f = fract(p);
+    let i = base.y * TILE + base.x;
+
+    let top = mix(tile[i], tile[i + 1], f.x);
+    let bottom = mix(tile[i + TILE], tile[i + TILE + 1], f.x);
+
+    return mix(top, bottom, f.y);
+}
+
+fn tap(origin: vec2<i32>, position: vec2<f32>, cached: bool, edge: bool) -> vec4<f32> {
+    if cached {
+        return sample_tile(origin, position);
+    }
+
+    return fetch(position / vec2<f32>(textureDimensions(u_texture)), edge);
+}
+
+// taps reaching past the apron sample the texture directly, which only
+// happens for the widest levels of large blurs
+fn is_cached(reach: f32) -> bool {
+    return reach + 1.0 <= f32(APRON);
+}
+
+@compute @workgroup_size(8, 8)
+fn downsample(
+    @builtin(workgroup_id) group: vec3<u32>,
+    @builtin(local_invocation_id) local: vec3<u32>,
+    @builtin(local_invocation_index) index: u32,
+) {
+    let origin = vec2<i32>(u_instance.atlas) + vec2<i32>(group.xy * WORKGROUP);
+    let offset = f32(u_texel.size) * u_instance.spread;
+    let cached = is_cached(offset);
+
+    if cached {
+        load_tile(origin, index, true);
+    }
+
+    let pixel = origin + vec2<i32>(local.xy);
+
+    // the capture only covers its own part of the texture
+    if any(vec2<f32>(pixel) >= u_instance.atlas + u_instance.capture_size) {
+        return;
+    }
+
+    let position = vec2<f32>(pixel) + 0.5;
+
+    const sample = vec2<f32>(-1.0, 1.0);
+
+    let color = 0.5 * tap(origin, position, cached, true) +
+            0.125 * (
+                tap(origin, position + offset * sample.xx, cached, true) +
+                tap(origin, position + offset * sample.yx, cached, true) +
+                tap(origin, position + offset * sample.xy, cached, true) +
+                tap(origin, position + offset * sample.yy, cached, true)
+            );
+
+    textureStore(u_target, pixel, color);
+}
+
+@compute @workgroup_size(8, 8)
+fn upsample(
+    @builtin(workgroup_id) group: vec3<u32>,
+    @builtin(local_invocation_id) local: vec3<u32>,
+    @builtin(local_invocation_index) index: u32,
+) {
+    let origin = vec2<i32>(u_instance.atlas) + vec2<i32>(group.xy * WORKGROUP);
+    let offset = f32(u_texel.size) * u_instance.spread;
+    let cached = is_cached(2.0 * offset);
+
+    if cached {
+        load_tile(origin, index, false);
+    }
+
+    let pixel = origin + vec2<i32>(local.xy);
+
+    if any(vec2<f32>(pixel) >= u_instance.atlas + u_instance.capture_size) {
+        return;
+    }
+
+    let position = vec2<f32>(pixel) + 0.5;
+
+    const sample = vec2<f32>(-1.0, 1.0);
+    const sample2 = vec2<f32>(0.0, 2.0);
+
+    let color = (1.0 / 6.0) * (
+                    tap(origin, position + offset * sample.xx, cached, false) +
+                    tap(origin, position + offset * sample.yx, cached, false) +
+                    tap(origin, position + offset * sample.xy, cached, false) +
+                    tap(origin, position + offset * sample.yy, cached, false)
+                ) +
+                (1.0 / 12.0) * (
+                    tap(origin, position + offset * sample2.xy, cached, false) +
+                    tap(origin, position - offset * sample2.xy, cached, false) +
+                    tap(origin, position + offset * sample2.yx, cached, false) +
+                    tap(origin, position - offset * sample2.yx, cached, false)
+                );
+
+    textureStore(u_target, pixel, color);
+}
@@ -4,6 +4,8 @@
 struct Texel { size: u32, _pad0: vec4<u32>, _pad1: vec3<u32> };
 @group(1) @binding(0) var<uniform> u_texel: Texel;
 
+@group(2) @binding(0) var<uniform> u_instance: Instance;
+
 struct VertexInput {
     @builtin(vertex_index) vertex_index: u32,
 }
@@ -35,15 +37,18 @@ fn vs_main(input: VertexInput) -> VertexOutput {
 @fragment
 fn fs_main(input: VertexOutput) -> @location(0) vec4<f32> {
     var texel_size = vec2<f32>(1) / vec2<f32>(textureDimensions(u_texture));
-    var texel = texel_size * vec2<f32>(u_texel.size);
+    var texel = texel_size * vec2<f32>(u_texel.size) * u_instance.spread;
+
+    // the capture only covers its own part of the texture
+    let uv = (u_instance.atlas + input.uv * u_instance.capture_size) * texel_size;
 
     const sample = vec2<f32>(-1.0, 1.0);
 
-    return 0.5 * textureSample(u_texture, u_sampler, input.uv) + 
+    return 0.5 * sample_edge(uv) + 
             0.125 * (
-                textureSample(u_texture, u_sampler, input.uv+texel*sample.xx)+
-                textureSample(u_texture, u_sampler, input.uv+texel*sample.yx)+
-                textureSample(u_texture, u_sampler, input.uv+texel*sample.xy)+
-                textureSample(u_texture, u_sampler, input.uv+texel*sample.yy)
+                sample_edge(uv+texel*sample.xx)+
+                sample_edge(uv+texel*sample.yx)+
+                sample_edge(uv+texel*sample.xy)+
+                sample_edge(uv+texel*sample.yy)
             );
 }
\ No newline at end of file
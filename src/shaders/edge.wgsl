@@ -0,0 +1,45 @@
+// the edge handling shared by the passes of the chain, which sample the
+// level at an explicit lod so that compute shaders can use them too
+
+const EDGE_CLAMP: u32 = 0u;
+const EDGE_MIRROR: u32 = 1u;
+const EDGE_TRANSPARENT: u32 = 2u;
+
+// samples the texture, treating the texels outside of the part copied from
+// the framebuffer according to the edge mode
+//
+// colors stay premultiplied throughout the chain, so averaging them never
+// bleeds the color of transparent pixels into their neighbors
+fn sample_edge(uv: vec2<f32>) -> vec4<f32> {
+    // only the first pass reads the backdrop, later ones write the whole
+    // capture
+    let first = u_texel.size == 1u;
+    let valid = select(vec4<f32>(vec2<f32>(0.0), u_instance.capture_size), u_instance.valid, first) +
+            u_instance.atlas.xyxy;
+    let mode = select(EDGE_CLAMP, u_instance.edge_mode, first);
+
+    let dimensions = vec2<f32>(textureDimensions(u_texture));
+    let low = valid.xy / dimensions;
+    let high = valid.zw / dimensions;
+    let half_texel = 0.5 / dimensions;
+
+    let t = (uv - low) / max(high - low, vec2<f32>(1e-5));
+    let mirrored = low + (1.0 - abs(1.0 - (t - 2.0 * floor(t / 2.0)))) * (high - low);
+    let inside = all(uv >= low) && all(uv <= high);
+
+    let coords = clamp(select(uv, mirrored, mode == EDGE_MIRROR), low + half_texel, high - half_texel);
+    let color = decode(textureSampleLevel(u_texture, u_sampler, coords, 0.0), first && u_instance.linearize != 0u);
+
+    return select(color, vec4<f32>(0.0), mode == EDGE_TRANSPARENT && !inside);
+}
+
+// keeps samples within the capture, as the rest of the texture may hold
+// other regions
+fn sample_capture(uv: vec2<f32>) -> vec4<f32> {
+    let texel_size = vec2<f32>(1) / vec2<f32>(textureDimensions(u_texture));
+    let low = (u_instance.atlas + 0.5) * texel_size;
+    let high = (u_instance.atlas + u_instance.capture_size - 0.5) * texel_size;
+    let coords = clamp(uv, low, high);
+
+    return textureSampleLevel(u_texture, u_sampler, coords, 0.0);
+}
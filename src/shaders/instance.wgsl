@@ -0,0 +1,37 @@
+struct Instance {
+    tint: vec4<f32>,
+    border_color: vec4<f32>,
+    border_radius: vec4<f32>,
+    origin: vec2<f32>,
+    size: vec2<f32>,
+    capture_origin: vec2<f32>,
+    spread: f32,
+    opacity: f32,
+    blend_mode: u32,
+    noise: f32,
+    border_width: f32,
+    _pad0: f32,
+    gradient: vec4<f32>,
+    stops: array<vec4<f32>, 8>,
+    offsets: array<vec4<f32>, 2>,
+    stop_count: u32,
+    _pad1: u32,
+    _pad2: u32,
+    _pad3: u32,
+    shadow_color: vec4<f32>,
+    highlight_color: vec4<f32>,
+    shadow_offset: vec2<f32>,
+    shadow_blur: f32,
+    refraction: f32,
+    highlight_width: f32,
+    displacement: u32,
+    refract_backdrop: u32,
+    edge_mode: u32,
+    valid: vec4<f32>,
+    capture_size: vec2<f32>,
+    linearize: u32,
+    encoded: u32,
+    atlas: vec2<f32>,
+    _pad4: vec2<f32>,
+};
+
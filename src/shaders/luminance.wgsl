@@ -0,0 +1,54 @@
+@group(0) @binding(0) var u_texture: texture_2d<f32>;
+@group(0) @binding(1) var u_sampler: sampler;
+
+@group(1) @binding(0) var<uniform> u_instance: Instance;
+
+struct VertexInput {
+    @builtin(vertex_index) vertex_index: u32,
+}
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+}
+
+fn vertex_position(vertex_index: u32) -> vec2<f32> {
+    // #: 0 1 2 3 4 5
+    // x: 1 1 0 0 0 1
+    // y: 1 0 0 0 1 1
+    return vec2<f32>((vec2(1u, 2u) + vertex_index) % vec2(6u) < vec2(3u));
+}
+
+@vertex
+fn vs_main(input: VertexInput) -> VertexOutput {
+    var uv = vertex_position(input.vertex_index);
+
+    var out: VertexOutput;
+
+    out.position = vec4<f32>(uv * vec2(2.0, -2.0) + vec2(-1.0, 1.0), 0.0, 1.0);
+
+    return out;
+}
+
+const GRID: u32 = 8u;
+
+@fragment
+fn fs_main(input: VertexOutput) -> @location(0) vec4<f32> {
+    // only the region itself, without the margin captured around it
+    let dimensions = vec2<f32>(textureDimensions(u_texture));
+    let origin = (u_instance.origin - u_instance.capture_origin) / dimensions;
+    let size = u_instance.size / dimensions;
+
+    var total = 0.0;
+
+    for (var y = 0u; y < GRID; y++) {
+        for (var x = 0u; x < GRID; x++) {
+            let uv = origin + (vec2<f32>(f32(x), f32(y)) + 0.5) / f32(GRID) * size;
+            // relative luminance is defined on linear colors
+            let color = decode(textureSampleLevel(u_texture, u_sampler, uv, 0.0), u_instance.encoded != 0u);
+
+            total += dot(color.rgb, vec3<f32>(0.2126, 0.7152, 0.0722));
+        }
+    }
+
+    return vec4<f32>(total / f32(GRID * GRID), 0.0, 0.0, 1.0);
+}
@@ -0,0 +1,22 @@
+fn rounded_box_sdf(to_center: vec2<f32>, size: vec2<f32>, radius: f32) -> f32 {
+    return length(max(abs(to_center) - size + vec2<f32>(radius, radius), vec2<f32>(0.0, 0.0))) - radius;
+}
+
+fn select_border_radius(radi: vec4<f32>, position: vec2<f32>, center: vec2<f32>) -> f32 {
+    var rx = radi.x;
+    var ry = radi.y;
+    rx = select(radi.x, radi.y, position.x > center.x);
+    ry = select(radi.w, radi.z, position.x > center.x);
+    rx = select(rx, ry, position.y > center.y);
+    return rx;
+}
+
+// signed distance from `position` to the edge of a rounded rectangle of
+// `size` at the origin, negative inside
+fn region_distance(position: vec2<f32>, size: vec2<f32>, radii: vec4<f32>) -> f32 {
+    let half = size / 2.0;
+    let radius = min(select_border_radius(radii, position, half), min(half.x, half.y));
+
+    return rounded_box_sdf(position - half, half, radius);
+}
+
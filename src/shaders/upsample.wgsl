@@ -4,6 +4,8 @@
 struct Texel { size: u32, _pad0: vec4<u32>, _pad1: vec3<u32> };
 @group(1) @binding(0) var<uniform> u_texel: Texel;
 
+@group(2) @binding(0) var<uniform> u_instance: Instance;
+
 struct VertexInput {
     @builtin(vertex_index) vertex_index: u32,
 }
@@ -35,21 +37,24 @@ fn vs_main(input: VertexInput) -> VertexOutput {
 @fragment
 fn fs_main(input: VertexOutput) -> @location(0) vec4<f32> {
     var texel_size = vec2<f32>(1) / vec2<f32>(textureDimensions(u_texture));
-    var texel = texel_size * vec2<f32>(u_texel.size);
+    var texel = texel_size * vec2<f32>(u_texel.size) * u_instance.spread;
+
+    // the capture only covers its own part of the texture
+    let uv = (u_instance.atlas + input.uv * u_instance.capture_size) * texel_size;
 
     const sample = vec2<f32>(-1.0, 1.0);
     const sample2 = vec2<f32>(0.0, 2.0);
 
     return (1.0 / 6.0) * (
-                    textureSample(u_texture, u_sampler, input.uv+texel*sample.xx) +
-                    textureSample(u_texture, u_sampler, input.uv+texel*sample.yx) +
-                    textureSample(u_texture, u_sampler, input.uv+texel*sample.xy) +
-                    textureSample(u_texture, u_sampler, input.uv+texel*sample.yy)
+                    sample_capture(uv+texel*sample.xx) +
+                    sample_capture(uv+texel*sample.yx) +
+                    sample_capture(uv+texel*sample.xy) +
+                    sample_capture(uv+texel*sample.yy)
                 ) + 
                 (1.0 / 12.0) * (
-                    textureSample(u_texture, u_sampler, input.uv+texel*sample2.xy)+
-                    textureSample(u_texture, u_sampler, input.uv-texel*sample2.xy)+
-                    textureSample(u_texture, u_sampler, input.uv+texel*sample2.yx)+
-                    textureSample(u_texture, u_sampler, input.uv-texel*sample2.yx)
+                    sample_capture(uv+texel*sample2.xy)+
+                    sample_capture(uv-texel*sample2.xy)+
+                    sample_capture(uv+texel*sample2.yx)+
+                    sample_capture(uv-texel*sample2.yx)
                 );
 }
\ No newline at end of file
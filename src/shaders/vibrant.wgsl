@@ -0,0 +1,64 @@
+@group(0) @binding(0) var u_backdrop: texture_2d<f32>;
+@group(0) @binding(1) var u_backdrop_sampler: sampler;
+
+@group(1) @binding(0) var u_white: texture_2d<f32>;
+@group(1) @binding(1) var u_white_sampler: sampler;
+
+@group(2) @binding(0) var u_black: texture_2d<f32>;
+@group(2) @binding(1) var u_black_sampler: sampler;
+
+struct Uniforms { color: vec4<f32>, origin: vec2<f32>, blend_mode: u32, _pad0: u32 };
+@group(3) @binding(0) var<uniform> u_uniforms: Uniforms;
+
+struct VertexInput {
+    @builtin(vertex_index) vertex_index: u32,
+}
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+}
+
+fn vertex_position(vertex_index: u32) -> vec2<f32> {
+    // #: 0 1 2 3 4 5
+    // x: 1 1 0 0 0 1
+    // y: 1 0 0 0 1 1
+    return vec2<f32>((vec2(1u, 2u) + vertex_index) % vec2(6u) < vec2(3u));
+}
+
+@vertex
+fn vs_main(input: VertexInput) -> VertexOutput {
+    var uv = vertex_position(input.vertex_index);
+
+    var out: VertexOutput;
+
+    out.position = vec4<f32>(uv * vec2(2.0, -2.0) + vec2(-1.0, 1.0), 0.0, 1.0);
+
+    return out;
+}
+
+@fragment
+fn fs_main(input: VertexOutput) -> @location(0) vec4<f32> {
+    let texel = vec2<i32>(floor(input.position.xy - u_uniforms.origin));
+
+    let backdrop = textureLoad(u_backdrop, texel, 0);
+    let white = textureLoad(u_white, texel, 0);
+    let black = textureLoad(u_black, texel, 0);
+
+    // the content is drawn once in white and once in black over the same
+    // backdrop, so the difference is how much of each pixel its text covers,
+    // whatever the backdrop, while images keep their colors in both
+    let coverage = clamp(dot(white.rgb - black.rgb, vec3<f32>(1.0 / 3.0)), 0.0, 1.0);
+
+    let color = blend(u_uniforms.blend_mode, backdrop.rgb, u_uniforms.color.rgb);
+
+    return vec4(black.rgb + coverage * mix(backdrop.rgb, color, u_uniforms.color.a), backdrop.a);
+}
+
+// puts the backdrop back once the content was drawn in white, for it to be
+// drawn again in black
+@fragment
+fn fs_restore(input: VertexOutput) -> @location(0) vec4<f32> {
+    let texel = vec2<i32>(floor(input.position.xy - u_uniforms.origin));
+
+    return textureLoad(u_backdrop, texel, 0);
+}
@@ -0,0 +1,46 @@
+use iced_core::time::Duration;
+
+use std::sync::Mutex;
+
+static STATS: Mutex<BlurStats> = Mutex::new(BlurStats {
+    copy: None,
+    downsample: None,
+    upsample: None,
+    composite: None,
+    texture_bytes: 0,
+    passes: 0,
+});
+
+/// What the blurs of a frame cost the GPU.
+///
+/// The times are only measured on devices with the `TIMESTAMP_QUERY` and
+/// `TIMESTAMP_QUERY_INSIDE_ENCODERS` features, and lag a few frames behind as
+/// they are read back.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct BlurStats {
+    /// The time spent copying the backdrops out of the framebuffer, and the
+    /// level kept by blurs with a [`PowerPolicy`] other than `Live`.
+    ///
+    /// [`PowerPolicy`]: crate::PowerPolicy
+    pub copy: Option<Duration>,
+    /// The time spent in the downsample passes.
+    pub downsample: Option<Duration>,
+    /// The time spent in the upsample passes, up to the last one.
+    pub upsample: Option<Duration>,
+    /// The time spent in the last upsample passes, which composite the blurs
+    /// into the frame.
+    pub composite: Option<Duration>,
+    /// The bytes of the textures the blurs use.
+    pub texture_bytes: u64,
+    /// The number of render and compute passes recorded for the blurs.
+    pub passes: u32,
+}
+
+/// Returns the [`BlurStats`] of the last frame that was measured.
+pub fn blur_stats() -> BlurStats {
+    *STATS.lock().unwrap()
+}
+
+pub(crate) fn publish(stats: BlurStats) {
+    *STATS.lock().unwrap() = stats;
+}
@@ -0,0 +1,24 @@
+use std::sync::atomic::{self, AtomicBool};
+
+static REDUCE_TRANSPARENCY: AtomicBool = AtomicBool::new(false);
+
+/// Returns whether the blurs are replaced by mostly opaque fills of their
+/// tint.
+pub fn reduce_transparency() -> bool {
+    REDUCE_TRANSPARENCY.load(atomic::Ordering::Relaxed)
+}
+
+/// Replaces every blur with a mostly opaque fill of its tint, and draws vibrant
+/// content in its color, like the "reduce transparency" preference of most
+/// operating systems.
+///
+/// A [`Blur`] or [`Vibrant`] content can override it with
+/// [`Blur::reduce_transparency`] and [`Vibrant::reduce_transparency`].
+///
+/// [`Blur`]: crate::Blur
+/// [`Blur::reduce_transparency`]: crate::Blur::reduce_transparency
+/// [`Vibrant`]: crate::Vibrant
+/// [`Vibrant::reduce_transparency`]: crate::Vibrant::reduce_transparency
+pub fn set_reduce_transparency(reduce: bool) {
+    REDUCE_TRANSPARENCY.store(reduce, atomic::Ordering::Relaxed);
+}
@@ -0,0 +1,230 @@
+use crate::BlendMode;
+use crate::mode::{self, Mode};
+use crate::shader;
+use crate::transparency;
+
+use iced_core::widget::tree::{self, Tree};
+use iced_core::{Clipboard, Color, Element, Event, Layout, Length, Rectangle, Shell, Size, Widget};
+use iced_core::{layout, mouse, renderer};
+use iced_widget::renderer::wgpu::primitive;
+
+pub fn vibrant<'a, Message, Theme, Renderer>(
+    content: impl Into<Element<'a, Message, Theme, Renderer>>,
+) -> Vibrant<'a, Message, Theme, Renderer> {
+    Vibrant::new(content)
+}
+
+/// Draws text and icons tinted by the blurred backdrop underneath, like the
+/// vibrant labels of a translucent sidebar.
+///
+/// The content is drawn twice over the backdrop, with a white and then a
+/// black text color, and the difference between both gives its coverage. The
+/// text is then replaced by the [`Vibrant::color`] blended over the backdrop
+/// with the [`Vibrant::blend_mode`], while images and icons that do not use
+/// the text color keep their own.
+pub struct Vibrant<'a, Message, Theme, Renderer> {
+    content: Element<'a, Message, Theme, Renderer>,
+    color: Color,
+    blend_mode: BlendMode,
+    reduce_transparency: Option<bool>,
+}
+
+impl<'a, Message, Theme, Renderer> Vibrant<'a, Message, Theme, Renderer> {
+    pub fn new(content: impl Into<Element<'a, Message, Theme, Renderer>>) -> Self {
+        Self {
+            content: content.into(),
+            color: Color::WHITE,
+            blend_mode: BlendMode::Overlay,
+            reduce_transparency: None,
+        }
+    }
+
+    /// Sets the color the content is drawn in, before it is blended over the
+    /// backdrop. White by default.
+    pub fn color(self, color: impl Into<Color>) -> Self {
+        Self {
+            color: color.into(),
+            ..self
+        }
+    }
+
+    /// Sets the [`BlendMode`] the color of the content is blended over the
+    /// backdrop with. [`BlendMode::Overlay`] by default.
+    pub fn blend_mode(self, blend_mode: BlendMode) -> Self {
+        Self { blend_mode, ..self }
+    }
+
+    /// Draws the content in its color without blending it over the backdrop,
+    /// overriding the global [`set_reduce_transparency`] setting.
+    ///
+    /// [`set_reduce_transparency`]: crate::set_reduce_transparency
+    pub fn reduce_transparency(self, reduce: bool) -> Self {
+        Self {
+            reduce_transparency: Some(reduce),
+            ..self
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct State {
+    id: shader::Id,
+    mode: mode::Watch,
+}
+
+impl<Message, Theme, Renderer> Widget<Message, Theme, Renderer>
+    for Vibrant<'_, Message, Theme, Renderer>
+where
+    Renderer: iced_core::Renderer + primitive::Renderer,
+{
+    fn tag(&self) -> tree::Tag {
+        tree::Tag::of::<State>()
+    }
+
+    fn state(&self) -> tree::State {
+        tree::State::new(State::default())
+    }
+
+    fn children(&self) -> Vec<Tree> {
+        vec![Tree::new(&self.content)]
+    }
+
+    fn diff(&self, tree: &mut Tree) {
+        tree.diff_children(std::slice::from_ref(&self.content));
+    }
+
+    fn size(&self) -> Size<Length> {
+        self.content.as_widget().size()
+    }
+
+    fn layout(
+        &self,
+        tree: &mut Tree,
+        renderer: &Renderer,
+        limits: &layout::Limits,
+    ) -> layout::Node {
+        self.content
+            .as_widget()
+            .layout(&mut tree.children[0], renderer, limits)
+    }
+
+    fn update(
+        &mut self,
+        tree: &mut Tree,
+        event: &Event,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        renderer: &Renderer,
+        clipboard: &mut dyn Clipboard,
+        shell: &mut Shell<'_, Message>,
+        viewport: &Rectangle,
+    ) {
+        if tree.state.downcast_mut::<State>().mode.changed() {
+            shell.request_redraw();
+        }
+
+        self.content.as_widget_mut().update(
+            &mut tree.children[0],
+            event,
+            layout,
+            cursor,
+            renderer,
+            clipboard,
+            shell,
+            viewport,
+        );
+    }
+
+    fn mouse_interaction(
+        &self,
+        tree: &Tree,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        viewport: &Rectangle,
+        renderer: &Renderer,
+    ) -> mouse::Interaction {
+        self.content.as_widget().mouse_interaction(
+            &tree.children[0],
+            layout,
+            cursor,
+            viewport,
+            renderer,
+        )
+    }
+
+    fn draw(
+        &self,
+        tree: &Tree,
+        renderer: &mut Renderer,
+        theme: &Theme,
+        _style: &renderer::Style,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        viewport: &Rectangle,
+    ) {
+        let state = tree.state.downcast_ref::<State>();
+        let bounds = layout.bounds();
+
+        let reduce_transparency = self
+            .reduce_transparency
+            .unwrap_or_else(transparency::reduce_transparency);
+
+        let draw_content = |renderer: &mut Renderer, text_color| {
+            self.content.as_widget().draw(
+                &tree.children[0],
+                renderer,
+                theme,
+                &renderer::Style { text_color },
+                layout,
+                cursor,
+                viewport,
+            );
+        };
+
+        // without a backdrop to blend with, the content is drawn in its color
+        if reduce_transparency || mode::mode() == Some(Mode::Fallback) {
+            draw_content(renderer, self.color);
+
+            return;
+        }
+
+        // every step gets its own layer, which renders after the previous
+        // one, so the quads of the content are neither captured with the
+        // backdrop nor left over from the white pass
+        renderer.with_layer(bounds, |renderer| {
+            renderer.draw_primitive(
+                bounds,
+                shader::vibrant::Capture::new(state.id, self.color, self.blend_mode),
+            );
+        });
+
+        renderer.with_layer(bounds, |renderer| {
+            draw_content(renderer, Color::WHITE);
+        });
+
+        renderer.with_layer(bounds, |renderer| {
+            renderer.draw_primitive(bounds, shader::vibrant::Swap::new(state.id));
+        });
+
+        renderer.with_layer(bounds, |renderer| {
+            draw_content(renderer, Color::BLACK);
+        });
+
+        renderer.with_layer(bounds, |renderer| {
+            renderer.draw_primitive(bounds, shader::vibrant::Composite::new(state.id));
+        });
+    }
+}
+
+impl<'a, Message, Theme, Renderer> From<Vibrant<'a, Message, Theme, Renderer>>
+    for Element<'a, Message, Theme, Renderer>
+where
+    Message: 'a,
+    Theme: 'a,
+    Renderer: 'a,
+    Renderer: iced_core::Renderer + primitive::Renderer,
+{
+    fn from(vibrant: Vibrant<'a, Message, Theme, Renderer>) -> Self {
+        Element::new(vibrant)
+    }
+}